@@ -0,0 +1,7 @@
+#version 100
+
+varying lowp vec4 out_color;
+
+void main() {
+    gl_FragColor = out_color;
+}
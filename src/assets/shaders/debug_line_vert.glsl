@@ -0,0 +1,13 @@
+#version 100
+
+attribute vec3 in_pos;
+attribute vec4 in_color;
+
+varying lowp vec4 out_color;
+
+uniform mat4 view_proj;
+
+void main() {
+    gl_Position = view_proj * vec4(in_pos, 1.0);
+    out_color = in_color;
+}
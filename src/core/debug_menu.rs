@@ -0,0 +1,110 @@
+use glam::{Vec2, Vec4};
+use miniquad::KeyCode;
+
+use crate::{
+    physics::physics_config::PhysicsConfig,
+    render::hud::{Hud, LINE_HEIGHT},
+};
+
+const TEXT_COLOR: Vec4 = Vec4::new(0.8, 0.8, 0.8, 1.0);
+const SELECTED_COLOR: Vec4 = Vec4::new(1.0, 0.85, 0.3, 1.0);
+
+// Keyboard-driven menu for tweaking PhysicsConfig at runtime, drawn over the scene.
+// F1 toggles, Up/Down selects, Left/Right adjusts the selected value. While it is open X sets
+// off a test blast ahead of the player, which Stage handles.
+pub struct PhysicsDebugMenu {
+    pub open: bool,
+    selected: usize,
+}
+
+impl PhysicsDebugMenu {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            selected: 0,
+        }
+    }
+
+    // Returns true if the config was changed and needs to be re-applied
+    pub fn handle_key(&mut self, keycode: KeyCode, config: &mut PhysicsConfig) -> bool {
+        if keycode == KeyCode::F1 {
+            self.open = !self.open;
+            return false;
+        }
+
+        if !self.open {
+            return false;
+        }
+
+        let key_count = PhysicsConfig::KEYS.len();
+        match keycode {
+            KeyCode::Up => {
+                self.selected = (self.selected + key_count - 1) % key_count;
+                false
+            }
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % key_count;
+                false
+            }
+            KeyCode::Left => self.adjust(config, -1.0),
+            KeyCode::Right => self.adjust(config, 1.0),
+            _ => false,
+        }
+    }
+
+    fn adjust(&self, config: &mut PhysicsConfig, direction: f32) -> bool {
+        let key = PhysicsConfig::KEYS[self.selected];
+        let Some(current) = config.get(key) else {
+            return false;
+        };
+
+        let next = if let Ok(value) = current.parse::<bool>() {
+            (!value).to_string()
+        } else if let Ok(value) = current.parse::<usize>() {
+            value.saturating_add_signed(direction as isize).to_string()
+        } else if let Ok(value) = current.parse::<f32>() {
+            // Step by 10% so the same keys work for thresholds and frequencies alike
+            let step = if value.abs() > f32::EPSILON {
+                value.abs() * 0.1
+            } else {
+                0.001
+            };
+            (value + step * direction).max(0.0).to_string()
+        } else {
+            return false;
+        };
+
+        config.set(key, &next).is_ok()
+    }
+
+    // In the top-left corner of the window, while the menu is open
+    pub fn draw(&self, hud: &mut Hud, config: &PhysicsConfig) {
+        if !self.open {
+            return;
+        }
+
+        let origin = Vec2::new(20.0, 20.0);
+        hud.text(
+            origin,
+            "Physics (F1 to close, X for a test blast)",
+            TEXT_COLOR,
+        );
+        for (index, key) in PhysicsConfig::KEYS.iter().enumerate() {
+            let (marker, color) = if index == self.selected {
+                (">", SELECTED_COLOR)
+            } else {
+                (" ", TEXT_COLOR)
+            };
+            hud.text(
+                origin + Vec2::Y * LINE_HEIGHT * (index + 1) as f32,
+                &format!(
+                    "{} {:<34} {}",
+                    marker,
+                    key,
+                    config.get(key).unwrap_or_default()
+                ),
+                color,
+            );
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use glam::{Mat3, Quat, Vec3};
+use hecs::{Entity, World};
+
+use crate::{
+    core::simulation::step_physics,
+    flight::{
+        flight_components::{
+            AccelerationControlCommand, FlightController, FlightControllerGains, SaturationMode,
+            TargetVelocity, ThrustSaturation, ThrusterLimits,
+        },
+        flight_controller_system::flight_controller_system,
+        pid::PidGains,
+        ship_class::ShipClass,
+        thruster_system::thruster_system,
+    },
+    physics::{
+        physics_components::{BoxCollider, Forces, InertiaProperties, MassProperties, Velocity},
+        physics_world::PhysicsWorld,
+        transform::Transform,
+    },
+};
+
+const DT: f32 = 1.0 / 60.0;
+
+// How long each step response runs for
+const EXPERIMENT_TIME: f32 = 8.0;
+
+// The step asks for this many seconds of full acceleration, so every hull gets pushed into
+// saturation the same way
+const STEP_TIME: f32 = 2.0;
+
+// Constant push against the step, as a share of the thrust on that axis, so the integral
+// term has something to do
+const DISTURBANCE_SHARE: f32 = 0.1;
+
+// Band around the step the response has to stay in to count as settled
+const SETTLING_BAND: f32 = 0.02;
+
+// Seconds of settling time one whole step of overshoot, or of error left at the end, is worth.
+// Leftover error costs even inside the settling band, so the integral term earns its keep.
+const OVERSHOOT_WEIGHT: f32 = 10.0;
+const FINAL_ERROR_WEIGHT: f32 = 100.0;
+
+// The pattern search stops once its steps are smaller than this factor, or it runs out of
+// experiments
+const MIN_SEARCH_FACTOR: f32 = 1.05;
+const MAX_EVALUATIONS: usize = 80;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ControlLoop {
+    Linear,
+    Angular,
+}
+
+// What the tuner knows about a hull, read off a ship spawned from its class
+struct Plant {
+    mass: f32,
+    inertia: Mat3,
+    limits: ThrusterLimits,
+}
+
+impl Plant {
+    // Local axes, each pointing to the side of the axis with the least authority
+    fn weakest_axes(&self, control_loop: ControlLoop) -> [Vec3; 3] {
+        [Vec3::X, Vec3::Y, Vec3::Z].map(|axis| {
+            let positive = self.authority(control_loop, axis);
+            let negative = self.authority(control_loop, -axis);
+            if negative < positive { -axis } else { axis }
+        })
+    }
+
+    // Largest acceleration along a local direction, one of the axes
+    fn authority(&self, control_loop: ControlLoop, axis: Vec3) -> f32 {
+        match control_loop {
+            ControlLoop::Linear => {
+                self.limits.force_limit_towards(axis).dot(axis.abs()) / self.mass
+            }
+            ControlLoop::Angular => {
+                let torque = self.limits.torque_limit_towards(axis).dot(axis.abs());
+                torque / (self.inertia * axis.abs()).dot(axis.abs())
+            }
+        }
+    }
+
+    // The most acceleration any axis can give, anything beyond is wasted on saturation
+    fn output_limit(&self, control_loop: ControlLoop) -> f32 {
+        [Vec3::X, Vec3::Y, Vec3::Z]
+            .into_iter()
+            .flat_map(|axis| [axis, -axis])
+            .map(|axis| self.authority(control_loop, axis))
+            .fold(0.0, f32::max)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct StepResponse {
+    // Overshoot and final error as a share of the step
+    overshoot: f32,
+    settling_time: f32,
+    final_error: f32,
+}
+
+impl StepResponse {
+    fn cost(&self) -> f32 {
+        self.settling_time
+            + self.overshoot * OVERSHOOT_WEIGHT
+            + self.final_error * FINAL_ERROR_WEIGHT
+    }
+}
+
+// Headless gain tuning, run with `cargo run --release -- --tune <ship class>`. Finds the
+// flight controller gains that give a class the quickest step responses without much
+// overshoot, by pattern search over step experiments on its weakest axes.
+pub fn run_tuning(class_name: &str) -> bool {
+    let Some(class) = ShipClass::by_name(class_name) else {
+        eprintln!(
+            "Unknown ship class '{}', expected one of {:?}",
+            class_name,
+            ShipClass::NAMES
+        );
+        return false;
+    };
+
+    let plant = measure_plant(&class);
+    println!(
+        "tune {}: mass {:.0} kg, inertia diagonal {:.0}, max acceleration {:.2} m/s^2 and {:.2} rad/s^2",
+        class.name,
+        plant.mass,
+        Vec3::new(
+            plant.inertia.x_axis.x,
+            plant.inertia.y_axis.y,
+            plant.inertia.z_axis.z
+        ),
+        plant.output_limit(ControlLoop::Linear),
+        plant.output_limit(ControlLoop::Angular),
+    );
+
+    let mut gains = class.gains;
+    gains.linear.output_limit = plant.output_limit(ControlLoop::Linear);
+    gains.angular.output_limit = plant.output_limit(ControlLoop::Angular);
+
+    for control_loop in [ControlLoop::Linear, ControlLoop::Angular] {
+        let tuned = tune_loop(&class, &plant, gains, control_loop);
+        match control_loop {
+            ControlLoop::Linear => gains.linear = tuned,
+            ControlLoop::Angular => gains.angular = tuned,
+        }
+    }
+
+    for (label, loop_gains) in [("linear", gains.linear), ("angular", gains.angular)] {
+        println!(
+            "tune {}: {:<7} PidGains::new({:.3}, {:.3}, {:.3}).with_output_limit({:.2})",
+            class.name, label, loop_gains.kp, loop_gains.ki, loop_gains.kd, loop_gains.output_limit
+        );
+    }
+
+    true
+}
+
+// Pattern search over kp, ki and kd in log space, starting from the class's current gains
+fn tune_loop(
+    class: &ShipClass,
+    plant: &Plant,
+    gains: FlightControllerGains,
+    control_loop: ControlLoop,
+) -> PidGains {
+    let loop_gains = |gains: &FlightControllerGains| match control_loop {
+        ControlLoop::Linear => gains.linear,
+        ControlLoop::Angular => gains.angular,
+    };
+    let with_terms = |terms: [f32; 3]| {
+        let mut candidate = gains;
+        let pid = match control_loop {
+            ControlLoop::Linear => &mut candidate.linear,
+            ControlLoop::Angular => &mut candidate.angular,
+        };
+        [pid.kp, pid.ki, pid.kd] = terms;
+        candidate
+    };
+
+    let evaluations = std::cell::Cell::new(0);
+    let evaluate = |terms: [f32; 3]| {
+        evaluations.set(evaluations.get() + 1);
+        loop_cost(class, plant, with_terms(terms), control_loop)
+    };
+
+    // Zero terms can't be scaled, so they start a little above zero
+    let start = loop_gains(&gains);
+    let mut best = [
+        start.kp.max(0.1),
+        start.ki.max(0.01 * start.kp.max(0.1)),
+        start.kd.max(0.01 * start.kp.max(0.1)),
+    ];
+    let start_cost = evaluate([start.kp, start.ki, start.kd]);
+    let mut best_cost = evaluate(best);
+
+    let mut factor = 2.0_f32;
+    while factor > MIN_SEARCH_FACTOR && evaluations.get() < MAX_EVALUATIONS {
+        let mut improved = false;
+        for term in 0..3 {
+            for scale in [factor, 1.0 / factor] {
+                let mut candidate = best;
+                candidate[term] *= scale;
+                let cost = evaluate(candidate);
+                if cost < best_cost {
+                    best = candidate;
+                    best_cost = cost;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            factor = factor.sqrt();
+        }
+    }
+
+    // A derivative term that barely helps isn't worth the noise it lets through
+    let without_derivative = [best[0], best[1], 0.0];
+    let cost = evaluate(without_derivative);
+    if cost <= best_cost * 1.01 {
+        best = without_derivative;
+        best_cost = cost;
+    }
+
+    println!(
+        "tune {}: {:?} cost {:.2} -> {:.2} after {} experiments",
+        class.name,
+        control_loop,
+        start_cost,
+        best_cost,
+        evaluations.get()
+    );
+
+    let tuned = with_terms(best);
+    for axis in plant.weakest_axes(control_loop) {
+        let response = step_response(class, plant, tuned, control_loop, axis);
+        println!(
+            "tune {}: {:?} step along {:?}: settles in {:.2} s, overshoot {:.1}%, final error {:.2}%",
+            class.name,
+            control_loop,
+            axis,
+            response.settling_time,
+            response.overshoot * 100.0,
+            response.final_error * 100.0
+        );
+    }
+
+    loop_gains(&tuned)
+}
+
+fn loop_cost(
+    class: &ShipClass,
+    plant: &Plant,
+    gains: FlightControllerGains,
+    control_loop: ControlLoop,
+) -> f32 {
+    plant
+        .weakest_axes(control_loop)
+        .into_iter()
+        .map(|axis| step_response(class, plant, gains, control_loop, axis).cost())
+        .sum()
+}
+
+// Starts a ship at rest, asks for a step in velocity along a local axis while a constant
+// disturbance pushes back, and measures how the velocity gets there
+fn step_response(
+    class: &ShipClass,
+    plant: &Plant,
+    gains: FlightControllerGains,
+    control_loop: ControlLoop,
+    axis: Vec3,
+) -> StepResponse {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+    let ship = spawn_ship(&mut world, class, gains);
+    step_physics(&mut world, &mut physics_world, DT);
+
+    let authority = plant.authority(control_loop, axis);
+    let step = authority * STEP_TIME;
+    {
+        let mut target = world
+            .get::<&mut TargetVelocity>(ship)
+            .expect("Ship should exist");
+        match control_loop {
+            ControlLoop::Linear => target.target_linear_velocity = axis * step,
+            ControlLoop::Angular => target.target_angular_velocity = axis * step,
+        }
+    }
+
+    let mut peak = 0.0_f32;
+    let mut settling_time = 0.0;
+    let mut error = 1.0;
+    let steps = (EXPERIMENT_TIME / DT) as usize;
+    for index in 0..steps {
+        flight_controller_system(&mut world, DT);
+        thruster_system(&mut world, DT);
+        {
+            let mut forces = world.get::<&mut Forces>(ship).expect("Ship should exist");
+            match control_loop {
+                ControlLoop::Linear => {
+                    forces.linear -= axis * authority * plant.mass * DISTURBANCE_SHARE;
+                }
+                ControlLoop::Angular => {
+                    let inertia = (plant.inertia * axis.abs()).dot(axis.abs());
+                    forces.torque -= axis * authority * inertia * DISTURBANCE_SHARE;
+                }
+            }
+        }
+        step_physics(&mut world, &mut physics_world, DT);
+
+        let velocity = world.get::<&Velocity>(ship).expect("Ship should exist");
+        let measured = match control_loop {
+            ControlLoop::Linear => velocity.linear.dot(axis),
+            ControlLoop::Angular => velocity.angular.dot(axis),
+        } / step;
+
+        peak = peak.max(measured);
+        error = (1.0 - measured).abs();
+        if error > SETTLING_BAND {
+            settling_time = (index + 1) as f32 * DT;
+        }
+    }
+
+    // Never settling costs more the further off it ended up
+    if error > SETTLING_BAND {
+        settling_time += error * EXPERIMENT_TIME;
+    }
+
+    StepResponse {
+        overshoot: (peak - 1.0).max(0.0),
+        settling_time,
+        final_error: error,
+    }
+}
+
+fn spawn_ship(world: &mut World, class: &ShipClass, gains: FlightControllerGains) -> Entity {
+    world.spawn((
+        Transform {
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        MassProperties::new(class.mass),
+        BoxCollider::new(class.hull_size.x, class.hull_size.y, class.hull_size.z),
+        Velocity::ZERO,
+        Forces::ZERO,
+        class.limits(),
+        class.thrusters.clone(),
+        TargetVelocity::new(Vec3::ZERO, Vec3::ZERO),
+        FlightController::new(gains),
+        AccelerationControlCommand::new(),
+        // Same as the AI ships, which are most of the ships flying with these gains
+        ThrustSaturation::new(SaturationMode::Uniform),
+    ))
+}
+
+// Spawns a ship of the class and reads back the mass, inertia and limits it ends up with
+fn measure_plant(class: &ShipClass) -> Plant {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+    let ship = spawn_ship(&mut world, class, class.gains);
+    step_physics(&mut world, &mut physics_world, DT);
+
+    let mut query = world
+        .query_one::<(&MassProperties, &InertiaProperties, &ThrusterLimits)>(ship)
+        .expect("Ship should exist");
+    let (mass_properties, inertia_properties, limits) =
+        query.get().expect("Ship should have its mass properties");
+
+    Plant {
+        mass: mass_properties.mass,
+        inertia: inertia_properties.inertia,
+        limits: *limits,
+    }
+}
@@ -1 +1,4 @@
+pub mod debug_menu;
+pub mod gain_tuning;
+pub mod simulation;
 pub mod stage;
@@ -0,0 +1,46 @@
+use hecs::World;
+
+use crate::{
+    destruction::fracture_system::fracture_system,
+    eva::eva_system::{eva_system, sync_new_characters},
+    physics::{
+        ccd_system::ccd_system,
+        explosion_system::explosion_system,
+        material_system::material_system,
+        physics_hooks::sync_active_hooks,
+        physics_system::physics_system,
+        physics_world::PhysicsWorld,
+        rotating_frame_system::rotating_frame_system,
+        sync_physics::{sync_ecs_to_rapier, sync_new_entities, sync_rapier_to_ecs},
+    },
+};
+
+// One physics frame, everything Stage::update runs after the flight systems. Shared with the
+// tests and the gain tuner so they simulate the same world the game does.
+pub fn step_physics(world: &mut World, physics_world: &mut PhysicsWorld, dt: f32) {
+    step_physics_with(world, physics_world, dt, |_world, _physics_world| {});
+}
+
+// Same as step_physics, with `after_step` called between the rapier step and the copy back into
+// the ECS, while the forces applied this frame are still on the entities. The debug overlay
+// reads them there.
+pub fn step_physics_with(
+    world: &mut World,
+    physics_world: &mut PhysicsWorld,
+    dt: f32,
+    after_step: impl FnOnce(&World, &PhysicsWorld),
+) {
+    fracture_system(world, physics_world);
+    sync_new_entities(world, physics_world);
+    sync_new_characters(world, physics_world);
+    ccd_system(world, physics_world);
+    material_system(world, physics_world);
+    explosion_system(world, physics_world);
+    eva_system(world, physics_world, dt);
+    rotating_frame_system(world, dt);
+    sync_ecs_to_rapier(world, physics_world);
+    sync_active_hooks(world, physics_world);
+    physics_system(physics_world, world, dt);
+    after_step(world, physics_world);
+    sync_rapier_to_ecs(world, physics_world);
+}
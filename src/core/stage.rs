@@ -7,23 +7,41 @@ use hecs::{Entity, World};
 use miniquad::{EventHandler, KeyCode, KeyMods, PassAction, RenderingBackend, window};
 use rand::Rng;
 
+use crate::core::debug_menu::PhysicsDebugMenu;
+use crate::core::simulation::step_physics_with;
+use crate::destruction::destruction_components::{Destructible, HullIntegrity};
+use crate::eva::eva_components::{Airlock, Boarded, EvaCharacter, EvaInput};
+use crate::eva::eva_system::{board_ship, exit_ship};
+use crate::flight::docking_components::DockingEvent;
 use crate::flight::flight_components::{
-    AccelerationControlCommand, FlightController, TargetVelocity, ThrusterLimits,
+    AccelerationControlCommand, FlightAssist, FlightAssistMode, FlightController, PilotInput,
+    SaturationMode, TargetVelocity, ThrustSaturation, ThrusterLimits,
 };
-use crate::flight::navigation_components::NavigationTarget;
+use crate::flight::formation_components::{Formation, FormationShape};
+use crate::flight::navigation_components::{
+    CollisionAvoidance, NavigationEvent, NavigationQueue, NavigationTarget, PathEvent, RouteMode,
+};
+use crate::flight::ship_class::ShipClass;
 use crate::flight::{
-    flight_controller_system::flight_controller_system, navigation_system::navigation_system,
-    thruster_system::thruster_system,
+    avoidance_system::avoidance_system, docking_system::docking_system,
+    flight_assist_system::flight_assist_system, flight_controller_system::flight_controller_system,
+    formation_system::formation_system, navigation_system::navigation_system,
+    path_planning_system::path_planning_system, pursuit_system::pursuit_system,
+    thruster_system::thruster_system, waypoint_system::waypoint_system,
 };
 
-use crate::physics::physics_components::{BoxCollider, Forces, MassProperties, Velocity};
-use crate::physics::physics_system::physics_system;
+use crate::physics::collision_layers::CollisionLayer;
+use crate::physics::physics_components::{
+    BoxCollider, CcdMode, CollisionFilter, ContinuousCollision, Explosion, Falloff, Forces,
+    MassProperties, PhysicsMaterial, Velocity,
+};
 use crate::physics::physics_world::PhysicsWorld;
-use crate::physics::sync_physics::{sync_ecs_to_rapier, sync_new_entities, sync_rapier_to_ecs};
 use crate::physics::transform::Transform;
 use crate::render::camera::Camera;
+use crate::render::hud::{Hud, LINE_HEIGHT};
 use crate::render::mesh_batch::Instance;
-use crate::render::mesh_manager::MeshManager;
+use crate::render::mesh_manager::{FractureSource, MeshManager};
+use crate::render::physics_debug_overlay::PhysicsDebugOverlay;
 use crate::render::render_components::Renderable;
 use crate::render::renderer::Renderer;
 
@@ -32,17 +50,21 @@ pub struct Stage {
     mesh_manager: MeshManager,
     renderer: Renderer,
     camera: Camera,
+    physics_overlay: PhysicsDebugOverlay,
+    hud: Hud,
 
     world: hecs::World,
     physics_world: PhysicsWorld,
 
     keys: HashSet<KeyCode>,
     mouse_pos: Vec2,
+    physics_menu: PhysicsDebugMenu,
 
     last_frame_time: Instant,
     elapsed_time: f32,
 
     player_entity: Entity,
+    pilot_entity: Entity,
 }
 
 impl Stage {
@@ -51,26 +73,34 @@ impl Stage {
         let renderer = Renderer::new(&mut ctx);
         let mesh_manager = MeshManager::new();
         let camera = Camera::new(800.0 / 600.0);
+        let physics_overlay = PhysicsDebugOverlay::new();
+        let hud = Hud::new();
         let world = World::new();
         let physics_world = PhysicsWorld::new();
         let keys = HashSet::new();
         let mouse_pos = Vec2::ZERO;
+        let physics_menu = PhysicsDebugMenu::new();
         let last_frame_time = Instant::now();
         let elapsed_time = 0.0;
         let player_entity = Entity::DANGLING;
+        let pilot_entity = Entity::DANGLING;
 
         Self {
             ctx,
             mesh_manager,
             renderer,
             camera,
+            physics_overlay,
+            hud,
             world,
             physics_world,
             keys,
             mouse_pos,
+            physics_menu,
             last_frame_time,
             elapsed_time,
             player_entity,
+            pilot_entity,
         }
     }
 
@@ -82,13 +112,19 @@ impl Stage {
         let albatross_mesh_id = self
             .mesh_manager
             .register_mesh(&mut self.ctx, "src/assets/meshes/albatross.obj");
-        let planet_mesh_id = self
+        let albatross_fragments = self.mesh_manager.register_fragments(
+            &mut self.ctx,
+            "src/assets/meshes/albatross.obj",
+            FractureSource::ObjGroups,
+        );
+        let _planet_mesh_id = self
             .mesh_manager
             .register_mesh(&mut self.ctx, "src/assets/meshes/planet.obj");
         let teapot_mesh_id = self
             .mesh_manager
             .register_mesh(&mut self.ctx, "src/assets/meshes/teapot.obj");
 
+        let albatross = ShipClass::albatross();
         let player_entity = self.world.spawn((
             Transform {
                 position: Vec3::ZERO,
@@ -96,23 +132,58 @@ impl Stage {
                 scale: Vec3::ONE,
             },
             Renderable::new(albatross_mesh_id),
-            MassProperties::new(5000.0),
-            BoxCollider::new(9.5484, 1.28, 4.3138),
+            MassProperties::new(albatross.mass),
+            BoxCollider::new(
+                albatross.hull_size.x,
+                albatross.hull_size.y,
+                albatross.hull_size.z,
+            ),
+            CollisionFilter::new(CollisionLayer::Ships),
+            ContinuousCollision::new(CcdMode::AboveSpeed(50.0)),
+            PhysicsMaterial::metal(),
             Velocity::ZERO,
             Forces::ZERO,
-            ThrusterLimits::new(
-                Vec3::new(25000.0, 25000.0, 50000.0) * 2.0,
-                Vec3::new(50000.0, 50000.0, 50000.0),
-            ),
+            albatross.limits(),
+            albatross.thrusters.clone(),
             TargetVelocity::new(Vec3::ZERO, Vec3::ZERO),
-            FlightController::new(1.0, 0.1, 2.0, 0.5),
+            FlightController::new(albatross.gains),
             AccelerationControlCommand::new(),
             NavigationTarget::new(Vec3::new(0.0, 0.0, 0.0), Quat::IDENTITY, 2.0),
         ));
+        self.world
+            .insert(
+                player_entity,
+                (
+                    Airlock::new(Vec3::new(0.0, 1.6, 0.0), 4.0),
+                    ThrustSaturation::new(SaturationMode::RotationPriority {
+                        rotation_priority: 0.8,
+                    }),
+                    FlightAssist::new().with_max_speed(albatross.max_speed),
+                    PilotInput::ZERO,
+                ),
+            )
+            .expect("Player should exist");
         self.player_entity = player_entity;
 
+        // The pilot starts aboard the player ship, the teapot stands in for an astronaut model
+        self.pilot_entity = self.world.spawn((
+            Transform {
+                position: Vec3::ZERO,
+                orientation: Quat::IDENTITY,
+                scale: Vec3::ONE * 0.3,
+            },
+            Renderable::new(teapot_mesh_id),
+            EvaCharacter::new(0.6, 0.3, 100.0),
+            EvaInput::ZERO,
+            Boarded {
+                ship: player_entity,
+            },
+        ));
+
+        let mut escorts = Vec::new();
+        let mut squadrons: Vec<(Entity, Vec<Entity>)> = Vec::new();
         let mut rand = rand::rng();
-        for i in 1..100 {
+        for index in 1..100 {
             let random_pos = Vec3::new(
                 rand.random_range(-1.0..1.0),
                 rand.random_range(-1.0..1.0),
@@ -129,26 +200,80 @@ impl Stage {
             .normalize()
                 * 200.0;
 
-            self.world.spawn((
+            let ship = self.world.spawn((
                 Transform {
                     position: random_pos,
                     orientation: Quat::IDENTITY,
                     scale: Vec3::ONE,
                 },
                 Renderable::new(albatross_mesh_id),
-                MassProperties::new(5000.0),
-                BoxCollider::new(9.5484, 1.28, 4.3138),
+                MassProperties::new(albatross.mass),
+                BoxCollider::new(
+                    albatross.hull_size.x,
+                    albatross.hull_size.y,
+                    albatross.hull_size.z,
+                ),
+                CollisionFilter::new(CollisionLayer::Ships),
+                ContinuousCollision::new(CcdMode::AboveSpeed(50.0)),
+                PhysicsMaterial::metal(),
                 Velocity::ZERO,
                 Forces::ZERO,
-                ThrusterLimits::new(
-                    Vec3::new(25000.0, 25000.0, 50000.0) * 2.0,
-                    Vec3::new(50000.0, 50000.0, 50000.0),
-                ),
+                albatross.limits(),
+                albatross.thrusters.clone(),
                 TargetVelocity::new(Vec3::ZERO, Vec3::ZERO),
-                FlightController::new(1.0, 0.1, 2.0, 0.5),
+                FlightController::new(albatross.gains),
                 AccelerationControlCommand::new(),
                 NavigationTarget::new(random_target, Quat::IDENTITY, 2.0),
             ));
+
+            self.world
+                .insert(
+                    ship,
+                    (
+                        HullIntegrity::new(100.0),
+                        Destructible::from_fragments(&albatross_fragments, 3.0),
+                        // Cheaper than rotation priority, which matters with this many ships
+                        ThrustSaturation::new(SaturationMode::Uniform),
+                        CollisionAvoidance::new(),
+                    ),
+                )
+                .expect("Ship should exist");
+
+            // The first few ships fly escort on the player. The rest fly in squadrons, each
+            // leader patrolling a ring or making flybys with the others in formation on it.
+            if index <= ESCORT_SLOTS.len() {
+                escorts.push(ship);
+            } else if (index - ESCORT_SLOTS.len() - 1).is_multiple_of(SQUADRON_SIZE) {
+                let route = if squadrons.len().is_multiple_of(2) {
+                    patrol_route(150.0 + squadrons.len() as f32 * 60.0, 20.0)
+                } else {
+                    flyby_route(random_pos, random_target)
+                };
+                self.world
+                    .insert_one(ship, route)
+                    .expect("Ship should exist");
+                squadrons.push((ship, Vec::new()));
+            } else if let Some((_leader, wingmen)) = squadrons.last_mut() {
+                wingmen.push(ship);
+            }
+        }
+
+        self.world
+            .insert_one(
+                player_entity,
+                Formation::new(FormationShape::Custom(ESCORT_SLOTS.to_vec()), escorts),
+            )
+            .expect("Player should exist");
+
+        for (squadron, (leader, wingmen)) in squadrons.into_iter().enumerate() {
+            let shape = match squadron % 3 {
+                0 => FormationShape::Wedge { spacing: 20.0 },
+                1 => FormationShape::LineAbreast { spacing: 20.0 },
+                _ => FormationShape::SphereShell { radius: 40.0 },
+            };
+            self.world
+                .insert_one(leader, Formation::new(shape, wingmen))
+                .expect("Leader should exist");
         }
 
         // self.world.spawn((
@@ -191,7 +316,113 @@ impl Stage {
         //     }
         // }
 
-        return true;
+        true
+    }
+}
+
+impl Stage {
+    fn is_on_eva(&self) -> bool {
+        !self
+            .world
+            .satisfies::<&Boarded>(self.pilot_entity)
+            .unwrap_or(true)
+    }
+
+    // Stops a ship where it is, so it waits for the pilot instead of chasing an old target
+    fn hold_position(&mut self, ship: Entity) {
+        let Ok(mut query) = self
+            .world
+            .query_one::<(&Transform, &mut NavigationTarget)>(ship)
+        else {
+            return;
+        };
+
+        if let Some((transform, nav_target)) = query.get() {
+            nav_target.target_position = transform.position;
+            nav_target.target_orientation = transform.orientation;
+        }
+    }
+
+    // Hands the pilot's controls to a ship, keeping whatever assist it was last flown with
+    fn take_controls(&mut self, ship: Entity) {
+        if !self.world.satisfies::<&FlightAssist>(ship).unwrap_or(true) {
+            self.world
+                .insert_one(ship, FlightAssist::new())
+                .expect("Ship should exist");
+        }
+        self.world
+            .insert_one(ship, PilotInput::ZERO)
+            .expect("Ship should exist");
+    }
+
+    // The HUD line for the player ship's flight assist
+    fn flight_assist_text(&self) -> Option<String> {
+        let assist = self.world.get::<&FlightAssist>(self.player_entity).ok()?;
+        let cruise = match (assist.mode, assist.match_target) {
+            (FlightAssistMode::Cruise, Some(target)) => {
+                format!(" matching {:?} at {:.0} m/s", target, assist.cruise_speed())
+            }
+            (FlightAssistMode::Cruise, None) => format!(
+                " at {:.0}% throttle, {:.0} m/s",
+                assist.throttle * 100.0,
+                assist.cruise_speed()
+            ),
+            _ => String::new(),
+        };
+        Some(format!(
+            "Flight assist: {}{}{}",
+            assist.mode.name(),
+            cruise,
+            if assist.rate_control {
+                ", rate control"
+            } else {
+                ""
+            }
+        ))
+    }
+
+    // Cruises at the speed of the closest ship within 30 degrees of the player's nose
+    fn match_speed_ahead(&mut self) {
+        let Ok((position, forward, forward_speed)) = self
+            .world
+            .query_one_mut::<(&Transform, &Velocity)>(self.player_entity)
+            .map(|(transform, velocity)| {
+                let forward = transform.orientation * Vec3::Z;
+                (transform.position, forward, velocity.linear.dot(forward))
+            })
+        else {
+            return;
+        };
+
+        let ahead = self
+            .world
+            .query::<&Transform>()
+            .with::<&ThrusterLimits>()
+            .iter()
+            .filter(|(ship, transform)| {
+                *ship != self.player_entity
+                    && (transform.position - position).angle_between(forward)
+                        < 30.0_f32.to_radians()
+            })
+            .min_by(|(_, first), (_, second)| {
+                first
+                    .position
+                    .distance(position)
+                    .total_cmp(&second.position.distance(position))
+            })
+            .map(|(ship, _transform)| ship);
+
+        if let Ok(mut assist) = self.world.get::<&mut FlightAssist>(self.player_entity) {
+            if assist.mode != FlightAssistMode::Cruise {
+                assist.mode = FlightAssistMode::Cruise;
+                assist.set_cruise_speed(forward_speed);
+            }
+            assist.match_target = ahead;
+        }
+
+        if ahead.is_none() {
+            println!("No ship ahead to match speed with");
+        }
     }
 }
 
@@ -230,20 +461,59 @@ impl EventHandler for Stage {
         //     // }
         // }
 
-        navigation_system(&mut self.world);
+        for event in path_planning_system(&mut self.world, &mut self.physics_world) {
+            if event == PathEvent::Unreachable(self.player_entity) {
+                println!("No route to the target, holding as close as it gets")
+            }
+        }
+        waypoint_system(&mut self.world);
+        formation_system(&mut self.world);
+        pursuit_system(&mut self.world);
+        for event in docking_system(&mut self.world) {
+            match event {
+                DockingEvent::Docked(ship) if ship == self.player_entity => println!("Docked"),
+                DockingEvent::Aborted(ship) if ship == self.player_entity => {
+                    println!("Docking aborted, lining up again")
+                }
+                DockingEvent::PortLost(ship) if ship == self.player_entity => {
+                    println!("Docking port lost")
+                }
+                _ => {}
+            }
+        }
+        for event in navigation_system(&mut self.world) {
+            match event {
+                NavigationEvent::Arrived(ship) if ship == self.player_entity => {
+                    println!("Arrived at target")
+                }
+                NavigationEvent::Departed(ship) if ship == self.player_entity => {
+                    println!("Departed from target")
+                }
+                _ => {}
+            }
+        }
+        flight_assist_system(&mut self.world, delta_time);
+        avoidance_system(&mut self.world, delta_time);
         flight_controller_system(&mut self.world, delta_time);
-        thruster_system(&mut self.world);
-
-        sync_new_entities(&mut self.world, &mut self.physics_world);
-        sync_ecs_to_rapier(&self.world, &mut self.physics_world);
-        physics_system(&mut self.physics_world, delta_time);
-
-        sync_rapier_to_ecs(&mut self.world, &mut self.physics_world);
-
-        if let Ok(transform) = self.world.get::<&Transform>(self.player_entity) {
-            if self.camera.position.distance(transform.position) > 10.0 {
+        thruster_system(&mut self.world, delta_time);
+
+        step_physics_with(
+            &mut self.world,
+            &mut self.physics_world,
+            delta_time,
+            |world, physics_world| self.physics_overlay.collect(world, physics_world),
+        );
+
+        let (controlled, camera_distance) = if self.is_on_eva() {
+            (self.pilot_entity, 4.0)
+        } else {
+            (self.player_entity, 10.0)
+        };
+
+        if let Ok(transform) = self.world.get::<&Transform>(controlled) {
+            if self.camera.position.distance(transform.position) > camera_distance {
                 let direction = (self.camera.position - transform.position).normalize();
-                self.camera.position = transform.position + direction * 10.0;
+                self.camera.position = transform.position + direction * camera_distance;
             }
 
             self.camera.target = transform.position;
@@ -251,72 +521,68 @@ impl EventHandler for Stage {
 
         let mut linear_move = Vec3::ZERO;
         if self.keys.contains(&KeyCode::W) {
-            linear_move.z += 0.1;
+            linear_move.z += 1.0;
         }
         if self.keys.contains(&KeyCode::A) {
-            linear_move.x += 0.1;
+            linear_move.x += 1.0;
         }
         if self.keys.contains(&KeyCode::S) {
-            linear_move.z -= 0.1;
+            linear_move.z -= 1.0;
         }
         if self.keys.contains(&KeyCode::D) {
-            linear_move.x -= 0.1;
+            linear_move.x -= 1.0;
         }
         if self.keys.contains(&KeyCode::Space) {
-            linear_move.y += 0.1;
+            linear_move.y += 1.0;
         }
         if self.keys.contains(&KeyCode::LeftShift) {
-            linear_move.y -= 0.1;
+            linear_move.y -= 1.0;
         }
 
-        let mut delta_rot = Quat::IDENTITY;
+        let mut turn = Vec3::ZERO;
         if self.keys.contains(&KeyCode::I) {
-            delta_rot *= Quat::from_rotation_x(0.01);
+            turn.x += 1.0;
         }
         if self.keys.contains(&KeyCode::K) {
-            delta_rot *= Quat::from_rotation_x(-0.01);
+            turn.x -= 1.0;
         }
         if self.keys.contains(&KeyCode::J) {
-            delta_rot *= Quat::from_rotation_y(0.01);
+            turn.y += 1.0;
         }
         if self.keys.contains(&KeyCode::L) {
-            delta_rot *= Quat::from_rotation_y(-0.01);
+            turn.y -= 1.0;
         }
         if self.keys.contains(&KeyCode::E) {
-            delta_rot *= Quat::from_rotation_z(0.01);
+            turn.z += 1.0;
         }
         if self.keys.contains(&KeyCode::Q) {
-            delta_rot *= Quat::from_rotation_z(-0.01);
-        }
-
-        if let Ok(mut nav_target) = self.world.get::<&mut NavigationTarget>(self.player_entity) {
-            nav_target.target_position += linear_move * 2.0;
-
-            nav_target.target_orientation = (nav_target.target_orientation * delta_rot).normalize();
+            turn.z -= 1.0;
         }
 
-        if self.keys.contains(&KeyCode::M) {
-            if let Ok(mut nav_target) = self.world.get::<&mut NavigationTarget>(self.player_entity)
-            {
-                nav_target.target_orientation = Quat::IDENTITY;
+        if self.is_on_eva() {
+            if let Ok(mut input) = self.world.get::<&mut EvaInput>(self.pilot_entity) {
+                input.local_move = linear_move.normalize_or_zero();
+                input.local_rotation = Quat::from_scaled_axis(turn * 0.01);
             }
+        } else if let Ok(mut input) = self.world.get::<&mut PilotInput>(self.player_entity) {
+            input.local_move = linear_move;
+            input.local_turn = turn;
         }
 
-        for (entity, nav_target) in self.world.query_mut::<&mut NavigationTarget>() {
-            if self.player_entity == entity {
-                continue;
-            }
-
-            let angle = self.elapsed_time * 0.5; // 0.5 is the speed, adjust as needed
-            let radius = (((entity.id()) * 100) + 50) as f32; // adjust radius as needed
-
-            nav_target.target_position = Vec3::new(0.0, radius * angle.cos(), radius * angle.sin());
+        if self.keys.contains(&KeyCode::M)
+            && !self.is_on_eva()
+            && let Ok(mut nav_target) = self.world.get::<&mut NavigationTarget>(self.player_entity)
+        {
+            nav_target.target_orientation = Quat::IDENTITY;
         }
     }
 
     fn draw(&mut self) {
-        for (_entity, (transform, render_comp)) in
-            self.world.query::<(&Transform, &Renderable)>().iter()
+        for (_entity, (transform, render_comp)) in self
+            .world
+            .query::<(&Transform, &Renderable)>()
+            .without::<&Boarded>()
+            .iter()
         {
             self.mesh_manager.submit_mesh_instance(
                 Instance::new(transform.to_mat4(), Vec4::new(0.0, 0.0, 0.0, 1.0)),
@@ -334,6 +600,23 @@ impl EventHandler for Stage {
 
         self.renderer
             .draw(&mut self.ctx, &mut self.mesh_manager, view_proj);
+        self.renderer
+            .draw_debug_lines(&mut self.ctx, &self.physics_overlay.lines, view_proj);
+
+        self.hud.clear();
+        if !self.is_on_eva()
+            && let Some(text) = self.flight_assist_text()
+        {
+            let (_width, height) = window::screen_size();
+            self.hud.text(
+                Vec2::new(20.0, height - 20.0 - LINE_HEIGHT),
+                &text,
+                Vec4::new(0.6, 1.0, 0.6, 1.0),
+            );
+        }
+        self.physics_menu
+            .draw(&mut self.hud, &self.physics_world.config);
+        self.renderer.draw_hud(&mut self.ctx, &self.hud.lines);
 
         self.ctx.end_render_pass();
         self.ctx.commit_frame();
@@ -341,6 +624,91 @@ impl EventHandler for Stage {
 
     fn key_down_event(&mut self, keycode: KeyCode, _keymods: KeyMods, _repeat: bool) {
         self.keys.insert(keycode);
+
+        if keycode == KeyCode::F2 {
+            self.physics_overlay.toggle();
+        }
+
+        // Leave the ship through its airlock, or board whichever airlock is in reach
+        if keycode == KeyCode::F {
+            if self.is_on_eva() {
+                if let Some(ship) =
+                    board_ship(&mut self.world, &mut self.physics_world, self.pilot_entity)
+                {
+                    self.player_entity = ship;
+                    self.hold_position(ship);
+                    self.take_controls(ship);
+                } else {
+                    println!("EVA: no airlock in reach");
+                }
+            } else {
+                // Let go of the controls, a decoupled ship keeps drifting
+                if let Ok(mut input) = self.world.get::<&mut PilotInput>(self.player_entity) {
+                    *input = PilotInput::ZERO;
+                }
+                self.hold_position(self.player_entity);
+                exit_ship(&mut self.world, self.pilot_entity);
+            }
+        }
+
+        // Cycle the flight assist mode, and switch turning between attitude hold and rates
+        if (keycode == KeyCode::V || keycode == KeyCode::R)
+            && !self.is_on_eva()
+            && let Ok(mut assist) = self.world.get::<&mut FlightAssist>(self.player_entity)
+        {
+            if keycode == KeyCode::V {
+                assist.mode = assist.mode.next();
+
+                // Cruise picks up at the speed the ship is already flying
+                if assist.mode == FlightAssistMode::Cruise
+                    && let Ok(transform) = self.world.get::<&Transform>(self.player_entity)
+                    && let Ok(velocity) = self.world.get::<&Velocity>(self.player_entity)
+                {
+                    assist.set_cruise_speed(velocity.linear.dot(transform.orientation * Vec3::Z));
+                }
+            } else {
+                assist.rate_control = !assist.rate_control;
+            }
+        }
+
+        if keycode == KeyCode::T && !self.is_on_eva() {
+            self.match_speed_ahead();
+        }
+
+        if keycode == KeyCode::B
+            && let Ok(mut character) = self.world.get::<&mut EvaCharacter>(self.pilot_entity)
+        {
+            character.mag_boots = !character.mag_boots;
+            println!(
+                "EVA: mag boots {}",
+                if character.mag_boots { "on" } else { "off" }
+            );
+        }
+
+        // Test blast 20m ahead of the player, only while the physics debug menu is open
+        if keycode == KeyCode::X && self.physics_menu.open {
+            let center = self
+                .world
+                .get::<&Transform>(self.player_entity)
+                .map(|transform| transform.position + transform.orientation * Vec3::Z * 20.0);
+
+            if let Ok(center) = center {
+                self.world.spawn((Explosion::new(
+                    center,
+                    40.0,
+                    2.0e5,
+                    150.0,
+                    Falloff::Quadratic,
+                ),));
+            }
+        }
+
+        if self
+            .physics_menu
+            .handle_key(keycode, &mut self.physics_world.config)
+        {
+            self.physics_world.apply_config();
+        }
     }
 
     fn key_up_event(&mut self, keycode: KeyCode, _keymods: KeyMods) {
@@ -351,3 +719,38 @@ impl EventHandler for Stage {
         self.mouse_pos = Vec2::new(x, y);
     }
 }
+
+// Escort positions around the player ship, in its local frame
+const ESCORT_SLOTS: [Vec3; 2] = [Vec3::new(15.0, 0.0, -10.0), Vec3::new(-15.0, 0.0, -10.0)];
+
+// Ships in each AI squadron, leader included
+const SQUADRON_SIZE: usize = 11;
+
+// A ring of pass-through waypoints around the origin in the YZ plane, each facing along the
+// route so the main engine does the pushing
+fn patrol_route(radius: f32, speed: f32) -> NavigationQueue {
+    let points: Vec<Vec3> = (0..8)
+        .map(|step| {
+            let angle = step as f32 / 8.0 * f32::consts::TAU;
+            Vec3::new(0.0, radius * angle.cos(), radius * angle.sin())
+        })
+        .collect();
+
+    let mut route = NavigationQueue::new().with_mode(RouteMode::Loop);
+    for (step, point) in points.iter().enumerate() {
+        let next = points[(step + 1) % points.len()];
+        let facing = Quat::from_rotation_arc(Vec3::Z, (next - *point).normalize());
+        route.add_waypoint(
+            NavigationTarget::new(*point, facing, 10.0).with_pass_through_speed(speed),
+        );
+    }
+    route
+}
+
+// Back and forth between two points, stopping at each end
+fn flyby_route(from: Vec3, to: Vec3) -> NavigationQueue {
+    let mut route = NavigationQueue::new().with_mode(RouteMode::PingPong);
+    route.add_waypoint(NavigationTarget::new(to, Quat::IDENTITY, 2.0));
+    route.add_waypoint(NavigationTarget::new(from, Quat::IDENTITY, 2.0));
+    route
+}
@@ -0,0 +1,60 @@
+use glam::Vec3;
+
+use crate::render::mesh_manager::{MeshFragment, MeshID};
+
+pub struct HullIntegrity {
+    pub current: f32,
+}
+
+impl HullIntegrity {
+    pub fn new(max: f32) -> Self {
+        Self { current: max }
+    }
+
+    pub fn apply_damage(&mut self, damage: f32) {
+        self.current = (self.current - damage).max(0.0);
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+#[derive(Clone)]
+pub struct DebrisPiece {
+    pub mesh_id: MeshID,
+    // Piece centre relative to the parent's origin, in the parent's unscaled local frame
+    pub local_offset: Vec3,
+    pub extents: Vec3,
+}
+
+// Replaced by its debris pieces once HullIntegrity runs out
+pub struct Destructible {
+    pub pieces: Vec<DebrisPiece>,
+    // Extra speed each piece gets away from the parent's centre on top of inherited velocity
+    pub separation_speed: f32,
+}
+
+impl Destructible {
+    pub fn new(pieces: Vec<DebrisPiece>, separation_speed: f32) -> Self {
+        Self {
+            pieces,
+            separation_speed,
+        }
+    }
+
+    pub fn from_fragments(fragments: &[MeshFragment], separation_speed: f32) -> Self {
+        let pieces = fragments
+            .iter()
+            .map(|fragment| DebrisPiece {
+                mesh_id: fragment.mesh_id,
+                local_offset: fragment.center,
+                extents: fragment.extents,
+            })
+            .collect();
+
+        Self::new(pieces, separation_speed)
+    }
+}
+
+pub struct Debris;
@@ -0,0 +1,100 @@
+use glam::Vec3;
+use hecs::{Entity, World};
+use rapier3d::prelude::RigidBodyHandle;
+
+use crate::{
+    destruction::destruction_components::{Debris, DebrisPiece, Destructible, HullIntegrity},
+    physics::{
+        collision_layers::CollisionLayer,
+        physics_components::{
+            BoxCollider, CcdMode, CollisionFilter, ContinuousCollision, Forces, MassProperties,
+            PhysicsMaterial, Velocity,
+        },
+        physics_world::PhysicsWorld,
+        sync_physics::remove_rigid_body,
+        transform::Transform,
+    },
+    render::render_components::Renderable,
+};
+
+// Smallest collider dimension for a piece, flat OBJ groups would otherwise get a zero-width box
+const MIN_PIECE_EXTENT: f32 = 0.1;
+
+// How far ahead contacts are predicted for a piece. A piece that thin can slip through a hull
+// between two steps at the speeds ships fly, and full CCD on every piece costs too much.
+const PIECE_SOFT_CCD: f32 = 2.0;
+
+// Replaces every destroyed Destructible with its debris pieces. Runs before sync_new_entities
+// so the pieces get their bodies on the same frame the parent's body is removed.
+pub fn fracture_system(world: &mut World, physics_world: &mut PhysicsWorld) {
+    let destroyed: Vec<Entity> = world
+        .query::<(&HullIntegrity, &Destructible)>()
+        .iter()
+        .filter(|(_entity, (integrity, _destructible))| integrity.is_destroyed())
+        .map(|(entity, _)| entity)
+        .collect();
+
+    for entity in destroyed {
+        let Ok((transform, velocity, mass_properties, destructible)) =
+            world.query_one_mut::<(&Transform, &Velocity, &MassProperties, &Destructible)>(entity)
+        else {
+            continue;
+        };
+
+        let position = transform.position;
+        let orientation = transform.orientation;
+        let scale = transform.scale;
+        let linear = velocity.linear;
+        let angular = velocity.angular;
+        let parent_mass = mass_properties.mass;
+        let separation_speed = destructible.separation_speed;
+        let pieces = destructible.pieces.clone();
+
+        let piece_extents =
+            |piece: &DebrisPiece| (piece.extents * scale).max(Vec3::splat(MIN_PIECE_EXTENT));
+        let total_volume: f32 = pieces
+            .iter()
+            .map(|piece| piece_extents(piece).element_product())
+            .sum();
+
+        for piece in &pieces {
+            let offset = orientation * (piece.local_offset * scale);
+            let extents = piece_extents(piece);
+
+            // Split the parent's mass between the pieces by bounding volume
+            let mass = parent_mass * extents.element_product() / total_volume;
+
+            // Each piece keeps the rigid-body velocity of the point it broke off from, and
+            // gets pushed outwards from the parent's centre on top of that
+            let away = offset.try_normalize().unwrap_or(orientation * Vec3::Y);
+            let mut forces = Forces::ZERO;
+            forces.apply_impulse_at_point(away * separation_speed * mass, position + offset);
+
+            world.spawn((
+                Transform {
+                    position: position + offset,
+                    orientation,
+                    scale,
+                },
+                Renderable::new(piece.mesh_id),
+                MassProperties::new(mass),
+                BoxCollider::new(extents.x, extents.y, extents.z),
+                CollisionFilter::new(CollisionLayer::Debris),
+                ContinuousCollision::with_soft_ccd(CcdMode::Never, PIECE_SOFT_CCD),
+                PhysicsMaterial::metal(),
+                Velocity {
+                    linear: linear + angular.cross(offset),
+                    angular,
+                },
+                forces,
+                Debris,
+            ));
+        }
+
+        if let Ok(rb_handle) = world.get::<&RigidBodyHandle>(entity).map(|handle| *handle) {
+            remove_rigid_body(physics_world, rb_handle);
+        }
+
+        world.despawn(entity).expect("Entity should exist");
+    }
+}
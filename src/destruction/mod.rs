@@ -0,0 +1,2 @@
+pub mod destruction_components;
+pub mod fracture_system;
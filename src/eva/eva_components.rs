@@ -0,0 +1,76 @@
+use glam::{Quat, Vec3};
+use hecs::Entity;
+
+// An astronaut moved by rapier's kinematic character controller rather than by forces
+pub struct EvaCharacter {
+    pub half_height: f32,
+    pub radius: f32,
+    pub mass: f32,
+    pub walk_speed: f32,
+    pub rcs_acceleration: f32,
+    pub mag_boots: bool,
+    // How far below the feet the boots still grab a hull
+    pub boot_reach: f32,
+    // Free-flight velocity in world space, relative to nothing
+    pub velocity: Vec3,
+    // Hull the boots are clamped to, and its surface normal under the feet
+    pub standing_on: Option<Entity>,
+    pub surface_normal: Vec3,
+}
+
+impl EvaCharacter {
+    pub fn new(half_height: f32, radius: f32, mass: f32) -> Self {
+        Self {
+            half_height,
+            radius,
+            mass,
+            walk_speed: 2.0,
+            rcs_acceleration: 1.5,
+            mag_boots: true,
+            boot_reach: 0.3,
+            velocity: Vec3::ZERO,
+            standing_on: None,
+            surface_normal: Vec3::Y,
+        }
+    }
+
+    // Distance from the capsule centre to the soles of the boots
+    pub fn foot_distance(&self) -> f32 {
+        self.half_height + self.radius
+    }
+}
+
+// Written by the input code each frame, in the character's local frame. Movement is a
+// direction with a length of at most one, walking on a hull and RCS thrust both scale it.
+pub struct EvaInput {
+    pub local_move: Vec3,
+    pub local_rotation: Quat,
+}
+
+impl EvaInput {
+    pub const ZERO: Self = Self {
+        local_move: Vec3::ZERO,
+        local_rotation: Quat::IDENTITY,
+    };
+}
+
+// A character riding inside a ship. It has no body and isn't drawn until it exits.
+pub struct Boarded {
+    pub ship: Entity,
+}
+
+// Where characters board and leave a ship, in the ship's local frame
+pub struct Airlock {
+    pub local_offset: Vec3,
+    // How close a character has to be to the airlock to board
+    pub reach: f32,
+}
+
+impl Airlock {
+    pub fn new(local_offset: Vec3, reach: f32) -> Self {
+        Self {
+            local_offset,
+            reach,
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use glam::{Quat, Vec3};
+use hecs::{Entity, World};
+use rapier3d::{
+    control::KinematicCharacterController,
+    na::{Quaternion, UnitQuaternion},
+    prelude::*,
+};
+
+use crate::{
+    eva::eva_components::{Airlock, Boarded, EvaCharacter, EvaInput},
+    physics::{
+        collision_layers::CollisionLayer,
+        physics_components::Velocity,
+        physics_hooks::{encode_user_data, entity_from_user_data},
+        physics_world::PhysicsWorld,
+        sync_physics::remove_rigid_body,
+        transform::Transform,
+    },
+};
+
+// How quickly a character standing on a hull turns its feet towards the surface, per second
+const BOOT_ALIGN_RATE: f32 = 8.0;
+
+// Gives every character that isn't boarded a kinematic capsule body
+pub fn sync_new_characters(world: &mut World, physics_world: &mut PhysicsWorld) {
+    let mut new_characters = Vec::new();
+
+    for (entity, (transform, character)) in world
+        .query::<(&Transform, &EvaCharacter)>()
+        .without::<&RigidBodyHandle>()
+        .without::<&Boarded>()
+        .iter()
+    {
+        let rb = RigidBodyBuilder::kinematic_position_based()
+            .position(to_isometry(transform.position, transform.orientation))
+            .build();
+
+        let layers = &physics_world.collision_layers;
+        let collider = ColliderBuilder::capsule_y(character.half_height, character.radius)
+            .mass(character.mass)
+            .user_data(encode_user_data(entity, None))
+            .collision_groups(layers.interaction_groups(CollisionLayer::Characters))
+            .solver_groups(layers.solver_groups(CollisionLayer::Characters))
+            .build();
+
+        let rb_handle = physics_world.bodies.insert(rb);
+        let collider_handle = physics_world.colliders.insert_with_parent(
+            collider,
+            rb_handle,
+            &mut physics_world.bodies,
+        );
+
+        new_characters.push((entity, rb_handle, collider_handle));
+    }
+
+    for (entity, rb_handle, collider_handle) in new_characters {
+        world
+            .insert(entity, (rb_handle, collider_handle))
+            .expect("Entity should exist");
+    }
+}
+
+// Moves characters from their EvaInput. With mag boots on and a hull under the feet the
+// character walks on the hull and is carried along with it, otherwise it drifts and the
+// input fires its RCS jetpack. Must run before physics_system, which moves the bodies.
+pub fn eva_system(world: &mut World, physics_world: &mut PhysicsWorld, dt: f32) {
+    follow_boarded_ships(world);
+
+    if dt <= 0.0 {
+        return;
+    }
+
+    // Bodies spawned since the last step aren't in the query pipeline yet
+    physics_world
+        .query_pipeline
+        .update(&physics_world.colliders);
+
+    let groups = physics_world
+        .collision_layers
+        .interaction_groups(CollisionLayer::Characters);
+
+    for (_entity, (transform, character, input, rb_handle)) in world
+        .query_mut::<(
+            &mut Transform,
+            &mut EvaCharacter,
+            &EvaInput,
+            &RigidBodyHandle,
+        )>()
+        .without::<&Boarded>()
+    {
+        let filter = QueryFilter::new()
+            .exclude_sensors()
+            .exclude_rigid_body(*rb_handle)
+            .groups(groups);
+
+        let mut orientation = (transform.orientation * input.local_rotation).normalize();
+        let up = orientation * Vec3::Y;
+        let local_move = input.local_move.clamp_length_max(1.0);
+
+        let ground = if character.mag_boots {
+            let ray = Ray::new(to_point(transform.position), to_vector(-up));
+            physics_world.query_pipeline.cast_ray_and_get_normal(
+                &physics_world.bodies,
+                &physics_world.colliders,
+                &ray,
+                character.foot_distance() + character.boot_reach,
+                true,
+                filter,
+            )
+        } else {
+            None
+        };
+
+        // Hull-relative movement, and the rigid motion of the hull under the feet plus
+        // whatever keeps the boots on it
+        let (desired, carry) = if let Some((collider_handle, hit)) = ground {
+            let collider = &physics_world.colliders[collider_handle];
+            let normal = Vec3::new(hit.normal.x, hit.normal.y, hit.normal.z);
+            let foot_point = transform.position - up * hit.time_of_impact;
+
+            let (surface_velocity, surface_spin) = collider
+                .parent()
+                .and_then(|parent| physics_world.bodies.get(parent))
+                .map_or((Vec3::ZERO, Vec3::ZERO), |rb| {
+                    let velocity = rb.velocity_at_point(&to_point(foot_point));
+                    let spin = rb.angvel();
+                    (
+                        Vec3::new(velocity.x, velocity.y, velocity.z),
+                        Vec3::new(spin.x, spin.y, spin.z),
+                    )
+                });
+
+            // Turn with the hull, and stand up along its surface
+            orientation = Quat::from_scaled_axis(surface_spin * dt) * orientation;
+            let align = Quat::from_rotation_arc(orientation * Vec3::Y, normal);
+            orientation = (Quat::IDENTITY.slerp(align, (BOOT_ALIGN_RATE * dt).min(1.0))
+                * orientation)
+                .normalize();
+
+            let walk_direction = orientation * local_move;
+            let walk = (walk_direction - normal * walk_direction.dot(normal)).normalize_or_zero()
+                * local_move.length()
+                * character.walk_speed;
+
+            let gap = hit.time_of_impact - character.foot_distance();
+
+            character.velocity = surface_velocity + walk;
+            character.standing_on = entity_from_user_data(collider.user_data);
+            character.surface_normal = normal;
+
+            (walk * dt, surface_velocity * dt - normal * gap)
+        } else {
+            character.velocity += orientation * local_move * character.rcs_acceleration * dt;
+            character.standing_on = None;
+
+            (character.velocity * dt, Vec3::ZERO)
+        };
+
+        let controller = KinematicCharacterController {
+            up: UnitVector::new_normalize(to_vector(orientation * Vec3::Y)),
+            snap_to_ground: None,
+            ..KinematicCharacterController::default()
+        };
+        let shape = Capsule::new_y(character.half_height, character.radius);
+        let mut position = transform.position;
+        let mut collisions = Vec::new();
+
+        // The hull moves the same way this step so it can't get in the way of riding along
+        // with it, but anything else can
+        if let Some((collider_handle, _hit)) = ground {
+            let movement = controller.move_shape(
+                dt,
+                &physics_world.bodies,
+                &physics_world.colliders,
+                &physics_world.query_pipeline,
+                &shape,
+                &to_isometry(position, transform.orientation),
+                to_vector(carry),
+                filter.exclude_collider(collider_handle),
+                |collision| collisions.push(collision),
+            );
+            position += to_vec3(movement.translation);
+        }
+
+        let movement = controller.move_shape(
+            dt,
+            &physics_world.bodies,
+            &physics_world.colliders,
+            &physics_world.query_pipeline,
+            &shape,
+            &to_isometry(position, transform.orientation),
+            to_vector(desired),
+            filter,
+            |collision| collisions.push(collision),
+        );
+
+        controller.solve_character_collision_impulses(
+            dt,
+            &mut physics_world.bodies,
+            &physics_world.colliders,
+            &physics_world.query_pipeline,
+            &shape,
+            character.mass,
+            &collisions,
+            filter,
+        );
+
+        let translation = to_vec3(movement.translation);
+
+        // Drifting into something stops the part of the velocity that went into it
+        if ground.is_none() && !collisions.is_empty() {
+            character.velocity = translation / dt;
+        }
+
+        transform.position = position + translation;
+        transform.orientation = orientation;
+
+        if let Some(rb) = physics_world.bodies.get_mut(*rb_handle) {
+            rb.set_next_kinematic_position(to_isometry(transform.position, orientation));
+        }
+    }
+}
+
+// Puts a character inside the nearest ship whose airlock is in reach, removing its body.
+// Returns the ship it boarded.
+pub fn board_ship(
+    world: &mut World,
+    physics_world: &mut PhysicsWorld,
+    character: Entity,
+) -> Option<Entity> {
+    let position = world.get::<&Transform>(character).ok()?.position;
+
+    let ship = world
+        .query::<(&Transform, &Airlock)>()
+        .iter()
+        .map(|(ship, (transform, airlock))| {
+            let airlock_position =
+                transform.position + transform.orientation * airlock.local_offset;
+            (ship, airlock_position.distance(position), airlock.reach)
+        })
+        .filter(|(_ship, distance, reach)| distance <= reach)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(ship, _distance, _reach)| ship)?;
+
+    if let Ok((rb_handle, _collider_handle)) =
+        world.remove::<(RigidBodyHandle, ColliderHandle)>(character)
+    {
+        remove_rigid_body(physics_world, rb_handle);
+    }
+
+    if let Ok(mut eva) = world.get::<&mut EvaCharacter>(character) {
+        eva.velocity = Vec3::ZERO;
+        eva.standing_on = None;
+    }
+
+    world
+        .insert_one(character, Boarded { ship })
+        .expect("Character should exist");
+
+    Some(ship)
+}
+
+// Puts a boarded character outside its ship's airlock, moving with the ship. If the ship is
+// gone the character is left where it last rode along. Its body is created by
+// sync_new_characters. Returns the ship it left.
+pub fn exit_ship(world: &mut World, character: Entity) -> Option<Entity> {
+    let ship = world.remove_one::<Boarded>(character).ok()?.ship;
+
+    let airlock = world
+        .query_one::<(&Transform, &Airlock, Option<&Velocity>)>(ship)
+        .ok()
+        .and_then(|mut query| {
+            query.get().map(|(transform, airlock, velocity)| {
+                let offset = transform.orientation * airlock.local_offset;
+                let velocity = velocity.map_or(Vec3::ZERO, |velocity| {
+                    velocity.linear + velocity.angular.cross(offset)
+                });
+
+                (transform.position + offset, transform.orientation, velocity)
+            })
+        });
+
+    if let Some((position, orientation, velocity)) = airlock
+        && let Ok((transform, eva)) =
+            world.query_one_mut::<(&mut Transform, &mut EvaCharacter)>(character)
+    {
+        transform.position = position;
+        transform.orientation = orientation;
+        eva.velocity = velocity;
+    }
+
+    Some(ship)
+}
+
+// Boarded characters ride along at their ship's position
+fn follow_boarded_ships(world: &mut World) {
+    let riders: Vec<(Entity, Vec3, Quat)> = world
+        .query::<&Boarded>()
+        .iter()
+        .filter_map(|(character, boarded)| {
+            let ship = world.get::<&Transform>(boarded.ship).ok()?;
+            Some((character, ship.position, ship.orientation))
+        })
+        .collect();
+
+    for (character, position, orientation) in riders {
+        if let Ok(mut transform) = world.get::<&mut Transform>(character) {
+            transform.position = position;
+            transform.orientation = orientation;
+        }
+    }
+}
+
+fn to_isometry(position: Vec3, orientation: Quat) -> Isometry<Real> {
+    let rotation = UnitQuaternion::from_quaternion(Quaternion::new(
+        orientation.w,
+        orientation.x,
+        orientation.y,
+        orientation.z,
+    ));
+    Isometry::from_parts(to_vector(position).into(), rotation)
+}
+
+fn to_point(v: Vec3) -> Point<Real> {
+    point![v.x, v.y, v.z]
+}
+
+fn to_vector(v: Vec3) -> Vector<Real> {
+    vector![v.x, v.y, v.z]
+}
+
+fn to_vec3(v: Vector<Real>) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
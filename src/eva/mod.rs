@@ -0,0 +1,2 @@
+pub mod eva_components;
+pub mod eva_system;
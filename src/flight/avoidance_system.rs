@@ -0,0 +1,128 @@
+use glam::Vec3;
+use hecs::{Entity, World};
+
+use crate::{
+    flight::{
+        flight_components::{TargetVelocity, ThrusterLimits},
+        navigation_components::CollisionAvoidance,
+        orca::{orca_plane, solve_velocity},
+    },
+    physics::{
+        physics_components::{BoxCollider, MassProperties, Velocity},
+        spatial_hash::SpatialHash,
+        transform::Transform,
+    },
+};
+
+const CELL_SIZE: f32 = 50.0;
+
+struct Body {
+    entity: Entity,
+    position: Vec3,
+    velocity: Vec3,
+    radius: f32,
+    avoids: bool,
+}
+
+// Bends the target velocity of every ship with CollisionAvoidance around the bodies near it,
+// using ORCA. Ships that also avoid take half the work each, everything else is dodged
+// entirely. The new velocity stays within what the ship's weakest thrust direction can reach
+// over the time horizon. Must run between navigation_system and flight_controller_system.
+pub fn avoidance_system(world: &mut World, dt: f32) {
+    if dt <= 0.0 {
+        return;
+    }
+
+    let bodies: Vec<Body> = world
+        .query::<(
+            &Transform,
+            &Velocity,
+            &BoxCollider,
+            Option<&CollisionAvoidance>,
+        )>()
+        .iter()
+        .map(
+            |(entity, (transform, velocity, collider, avoidance))| Body {
+                entity,
+                position: transform.position,
+                velocity: velocity.linear,
+                // Bounding sphere of the box
+                radius: collider.extents.length() * 0.5 * transform.scale.max_element(),
+                avoids: avoidance.is_some(),
+            },
+        )
+        .collect();
+
+    let mut spatial_hash = SpatialHash::new(CELL_SIZE);
+    for (index, body) in bodies.iter().enumerate() {
+        spatial_hash.insert(index, body.position);
+    }
+
+    let mut adjusted = Vec::new();
+    for body in bodies.iter().filter(|body| body.avoids) {
+        let Ok(mut query) = world.query_one::<(
+            &CollisionAvoidance,
+            &TargetVelocity,
+            &ThrusterLimits,
+            &MassProperties,
+        )>(body.entity) else {
+            continue;
+        };
+        let Some((avoidance, target, limits, mass_properties)) = query.get() else {
+            continue;
+        };
+
+        let mut neighbours = Vec::new();
+        spatial_hash.query(body.position, avoidance.neighbour_distance, |index| {
+            let neighbour = &bodies[index];
+            let distance = neighbour.position.distance(body.position);
+            if neighbour.entity != body.entity && distance < avoidance.neighbour_distance {
+                neighbours.push((distance, neighbour));
+            }
+        });
+        neighbours.sort_by(|a, b| a.0.total_cmp(&b.0));
+        neighbours.truncate(avoidance.max_neighbours);
+
+        let planes: Vec<_> = neighbours
+            .iter()
+            .map(|(_distance, neighbour)| {
+                orca_plane(
+                    body.velocity,
+                    neighbour.position - body.position,
+                    body.velocity - neighbour.velocity,
+                    body.radius + neighbour.radius + avoidance.safety_margin,
+                    avoidance.time_horizon,
+                    dt,
+                    if neighbour.avoids { 0.5 } else { 1.0 },
+                )
+            })
+            .collect();
+
+        let preferred = target.target_linear_velocity;
+        if planes
+            .iter()
+            .all(|plane| plane.normal.dot(preferred - plane.point) >= 0.0)
+        {
+            continue;
+        }
+
+        // The weakest direction bounds how far the velocity can be changed in any direction
+        let max_acceleration = limits
+            .max_force_positive
+            .min(limits.max_force_negative)
+            .min_element()
+            / mass_properties.mass;
+        let reach = max_acceleration * avoidance.time_horizon;
+
+        adjusted.push((
+            body.entity,
+            solve_velocity(&planes, body.velocity, reach, preferred),
+        ));
+    }
+
+    for (entity, velocity) in adjusted {
+        if let Ok(mut target) = world.get::<&mut TargetVelocity>(entity) {
+            target.target_linear_velocity = velocity;
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use glam::{Quat, Vec3};
+use hecs::Entity;
+
+// A place on a station or ship that others can dock at, in the frame of the body it is on.
+// Ships approach along the port's +Z axis and end up with their nose against the port.
+pub struct DockingPort {
+    pub position: Vec3,
+    pub orientation: Quat,
+    // How far out along the axis ships line up before going in
+    pub standoff_distance: f32,
+    // Half angle of the cone around the axis an approaching ship has to stay inside
+    pub corridor_angle: f32,
+    // Sideways error always allowed, so the cone doesn't close to nothing at the port
+    pub corridor_radius: f32,
+}
+
+impl DockingPort {
+    pub fn new(position: Vec3, orientation: Quat) -> Self {
+        Self {
+            position,
+            orientation,
+            standoff_distance: 50.0,
+            corridor_angle: 10.0_f32.to_radians(),
+            corridor_radius: 1.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DockingPhase {
+    // Flying to the standoff point and turning to face the port
+    Aligning,
+    // Closing in along the corridor
+    Approaching,
+    // Holding against the port
+    Docked,
+}
+
+// Autopilot that flies the ship into a docking port, driving its NavigationTarget through
+// docking_system
+pub struct Docking {
+    pub port: Entity,
+    pub phase: DockingPhase,
+    // Closing speed per metre left to go on the approach, and the most it is allowed to reach
+    pub approach_gain: f32,
+    pub max_approach_speed: f32,
+    // Approaches that strayed out of the corridor and went back to the standoff point
+    pub aborts: u32,
+}
+
+impl Docking {
+    pub fn new(port: Entity) -> Self {
+        Self {
+            port,
+            phase: DockingPhase::Aligning,
+            approach_gain: 0.2,
+            max_approach_speed: 5.0,
+            aborts: 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DockingEvent {
+    Docked(Entity),
+    Aborted(Entity),
+    // The port or the body it is on is gone
+    PortLost(Entity),
+}
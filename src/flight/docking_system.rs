@@ -0,0 +1,184 @@
+use glam::{Quat, Vec3};
+use hecs::{Entity, World};
+
+use crate::{
+    flight::{
+        docking_components::{Docking, DockingEvent, DockingPhase, DockingPort},
+        navigation_components::NavigationTarget,
+    },
+    physics::{
+        physics_components::{BoxCollider, Docked, Velocity},
+        transform::Transform,
+    },
+};
+
+// How close to the port, and how slow relative to it, a ship has to get to count as docked
+const DOCKED_DISTANCE: f32 = 0.5;
+const DOCKED_SPEED: f32 = 0.3;
+
+// Slowest the approach is let down to, so the last metre doesn't take forever
+const MIN_APPROACH_SPEED: f32 = 0.3;
+
+// How closely the ship has to match the standoff point's speed before it starts in
+const ALIGNED_SPEED: f32 = 0.5;
+
+// Where a docking ship should be flying this frame, all in world space
+struct DockingGoal {
+    target: NavigationTarget,
+    phase: DockingPhase,
+}
+
+// Flies each docking ship through its approach, by setting its NavigationTarget. The ship lines
+// up facing the port at the standoff point, then closes in along the port's axis at a speed
+// that drops with the distance left, holding against the port once there. Straying out of the
+// corridor sends it back to the standoff point to try again. Targets move and turn with the
+// body the port is on. Must run before navigation_system.
+pub fn docking_system(world: &mut World) -> Vec<DockingEvent> {
+    let mut goals: Vec<(Entity, Option<DockingGoal>)> = Vec::new();
+
+    for (entity, (transform, velocity, collider, docking)) in world
+        .query::<(&Transform, &Velocity, &BoxCollider, &Docking)>()
+        .with::<&NavigationTarget>()
+        .iter()
+    {
+        let goal = world
+            .query_one::<(&Transform, Option<&Velocity>, &DockingPort)>(docking.port)
+            .ok()
+            .and_then(|mut query| {
+                query.get().map(|(body_transform, body_velocity, port)| {
+                    plan_docking(
+                        transform,
+                        velocity,
+                        collider,
+                        docking,
+                        body_transform,
+                        body_velocity.unwrap_or(&Velocity::ZERO),
+                        port,
+                    )
+                })
+            });
+        goals.push((entity, goal));
+    }
+
+    let mut events = Vec::new();
+    for (entity, goal) in goals {
+        let Some(goal) = goal else {
+            // Stop where the ship is rather than chase a port that isn't there
+            if let Ok(mut nav_target) = world.get::<&mut NavigationTarget>(entity) {
+                nav_target.target_velocity = Vec3::ZERO;
+                nav_target.target_angular_velocity = Vec3::ZERO;
+                nav_target.max_speed = None;
+            }
+            world.remove_one::<Docking>(entity).ok();
+            world.remove_one::<Docked>(entity).ok();
+            events.push(DockingEvent::PortLost(entity));
+            continue;
+        };
+
+        let (docking, nav_target) = world
+            .query_one_mut::<(&mut Docking, &mut NavigationTarget)>(entity)
+            .expect("Docking ship should exist");
+        *nav_target = goal.target;
+
+        let mut docked_with = None;
+        match (docking.phase, goal.phase) {
+            (DockingPhase::Approaching, DockingPhase::Aligning) => {
+                docking.aborts += 1;
+                events.push(DockingEvent::Aborted(entity));
+            }
+            (DockingPhase::Approaching, DockingPhase::Docked) => {
+                docked_with = Some(docking.port);
+                events.push(DockingEvent::Docked(entity));
+            }
+            _ => {}
+        }
+        docking.phase = goal.phase;
+
+        // Sitting nose against the port, the ship would otherwise be pushed off the body
+        if let Some(body) = docked_with {
+            world
+                .insert_one(entity, Docked { with: body })
+                .expect("Docking ship should exist");
+        }
+    }
+
+    events
+}
+
+fn plan_docking(
+    transform: &Transform,
+    velocity: &Velocity,
+    collider: &BoxCollider,
+    docking: &Docking,
+    body_transform: &Transform,
+    body_velocity: &Velocity,
+    port: &DockingPort,
+) -> DockingGoal {
+    let port_orientation = body_transform.orientation * port.orientation;
+    let axis = port_orientation * Vec3::Z;
+    let port_position = body_transform.position + body_transform.orientation * port.position;
+
+    // Nose first into the port, so the ship's centre stops half a hull short of it
+    let docked_orientation = port_orientation * Quat::from_rotation_y(std::f32::consts::PI);
+    let nose = collider.extents.z * 0.5 * transform.scale.z;
+    let docked_position = port_position + axis * nose;
+    let standoff_position = docked_position + axis * port.standoff_distance;
+
+    // Points fixed to the body swing around with its spin
+    let point_velocity = |point: Vec3| {
+        body_velocity.linear + body_velocity.angular.cross(point - body_transform.position)
+    };
+
+    let to_ship = transform.position - port_position;
+    let along = to_ship.dot(axis);
+    let lateral = (to_ship - axis * along).length();
+    let allowed_lateral = port.corridor_radius + along.max(0.0) * port.corridor_angle.tan();
+
+    let mut phase = docking.phase;
+    match phase {
+        DockingPhase::Aligning => {
+            let aligned = transform.position.distance(standoff_position) < port.corridor_radius
+                && transform.orientation.angle_between(docked_orientation) < port.corridor_angle
+                && (velocity.linear - point_velocity(standoff_position)).length() < ALIGNED_SPEED;
+            if aligned {
+                phase = DockingPhase::Approaching;
+            }
+        }
+        DockingPhase::Approaching => {
+            if along < 0.0 || lateral > allowed_lateral {
+                phase = DockingPhase::Aligning;
+            } else if transform.position.distance(docked_position) < DOCKED_DISTANCE
+                && (velocity.linear - point_velocity(docked_position)).length() < DOCKED_SPEED
+            {
+                phase = DockingPhase::Docked;
+            }
+        }
+        DockingPhase::Docked => {}
+    }
+
+    let target = match phase {
+        DockingPhase::Aligning => {
+            NavigationTarget::new(standoff_position, docked_orientation, port.corridor_radius)
+                .with_angle_tolerance(port.corridor_angle)
+                .with_speed_tolerance(ALIGNED_SPEED)
+        }
+        DockingPhase::Approaching | DockingPhase::Docked => {
+            let remaining = transform.position.distance(docked_position);
+            let speed = (remaining * docking.approach_gain)
+                .clamp(MIN_APPROACH_SPEED, docking.max_approach_speed);
+            NavigationTarget::new(docked_position, docked_orientation, DOCKED_DISTANCE)
+                .with_speed_tolerance(DOCKED_SPEED)
+                .with_max_speed(speed)
+        }
+    };
+
+    let target_position = target.target_position;
+    DockingGoal {
+        target: NavigationTarget {
+            target_velocity: point_velocity(target_position),
+            target_angular_velocity: body_velocity.angular,
+            ..target
+        },
+        phase,
+    }
+}
@@ -0,0 +1,118 @@
+use glam::{Quat, Vec3};
+use hecs::{Entity, World};
+
+use crate::{
+    flight::{
+        flight_components::{
+            AccelerationControlCommand, FlightAssist, FlightAssistMode, PilotInput, TargetVelocity,
+            ThrusterLimits,
+        },
+        navigation_components::NavigationTarget,
+    },
+    physics::{
+        physics_components::{MassProperties, Velocity},
+        transform::Transform,
+    },
+};
+
+// How far full forward or back input moves the throttle, per second
+const THROTTLE_RATE: f32 = 0.5;
+
+// Flies each piloted ship from its PilotInput by its FlightAssist mode. Whatever the pilot
+// isn't flying by hand is left on a NavigationTarget that follows the ship, so switching back
+// holds it where it is. Must run between navigation_system and flight_controller_system.
+pub fn flight_assist_system(world: &mut World, dt: f32) {
+    // Velocities of the ships being speed matched, None for any that are gone
+    let matched: Vec<(Entity, Option<Vec3>)> = world
+        .query::<&FlightAssist>()
+        .iter()
+        .filter_map(|(entity, assist)| {
+            let target = assist.match_target?;
+            let velocity = world
+                .get::<&Velocity>(target)
+                .ok()
+                .map(|velocity| velocity.linear);
+            Some((entity, velocity))
+        })
+        .collect();
+
+    for (
+        entity,
+        (
+            transform,
+            assist,
+            input,
+            nav_target,
+            control_target,
+            command,
+            thruster_limits,
+            mass_properties,
+        ),
+    ) in world.query_mut::<(
+        &Transform,
+        &mut FlightAssist,
+        &PilotInput,
+        &mut NavigationTarget,
+        &mut TargetVelocity,
+        &mut AccelerationControlCommand,
+        &ThrusterLimits,
+        &MassProperties,
+    )>() {
+        let orientation = transform.orientation;
+
+        match assist.mode {
+            FlightAssistMode::PositionHold => {
+                nav_target.target_position +=
+                    orientation * input.local_move * assist.target_move_speed * dt;
+            }
+            FlightAssistMode::Coupled => {
+                nav_target.target_position = transform.position;
+                control_target.target_linear_velocity =
+                    orientation * input.local_move * assist.max_speed;
+            }
+            FlightAssistMode::Decoupled => {
+                // Full input is everything the thrusters on that side can give
+                nav_target.target_position = transform.position;
+                let local_force =
+                    input.local_move * thruster_limits.force_limit_towards(input.local_move);
+                command.linear_acceleration = orientation * local_force / mass_properties.mass;
+            }
+            FlightAssistMode::Cruise => {
+                nav_target.target_position = transform.position;
+
+                let match_velocity = matched
+                    .iter()
+                    .find(|(matching, _velocity)| *matching == entity)
+                    .map(|(_matching, velocity)| *velocity);
+                match match_velocity {
+                    // Taking the throttle back gives up matching
+                    _ if input.local_move.z != 0.0 => {
+                        assist.match_target = None;
+                        assist.throttle = (assist.throttle
+                            + input.local_move.z * THROTTLE_RATE * dt)
+                            .clamp(-1.0, 1.0);
+                    }
+                    Some(Some(velocity)) => {
+                        assist.set_cruise_speed(velocity.dot(orientation * Vec3::Z))
+                    }
+                    Some(None) => assist.match_target = None,
+                    None => {}
+                }
+
+                let strafe =
+                    Vec3::new(input.local_move.x, input.local_move.y, 0.0) * assist.strafe_speed;
+                control_target.target_linear_velocity =
+                    orientation * (strafe + Vec3::Z * assist.cruise_speed());
+            }
+        }
+
+        if assist.rate_control {
+            nav_target.target_orientation = orientation;
+            control_target.target_angular_velocity =
+                orientation * input.local_turn * assist.max_turn_rate;
+        } else {
+            let turn = Quat::from_scaled_axis(input.local_turn * assist.max_turn_rate * dt);
+            nav_target.target_orientation = (nav_target.target_orientation * turn).normalize();
+        }
+    }
+}
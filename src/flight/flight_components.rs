@@ -1,17 +1,238 @@
 use glam::Vec3;
+use hecs::Entity;
 
-// Maximum +/- linear and angular forces along XYZ axes
+use crate::flight::pid::{PidGains, PidState};
+
+// Maximum linear and angular forces along local XYZ axes, as magnitudes towards the positive
+// and the negative end of each axis
+#[derive(Copy, Clone)]
 pub struct ThrusterLimits {
-    pub max_force: Vec3,
-    pub max_torque: Vec3,
+    pub max_force_positive: Vec3,
+    pub max_force_negative: Vec3,
+    pub max_torque_positive: Vec3,
+    pub max_torque_negative: Vec3,
 }
 
 impl ThrusterLimits {
+    // The same limit both ways along each axis
     pub fn new(max_force: Vec3, max_torque: Vec3) -> Self {
+        Self::asymmetric(max_force, max_force, max_torque, max_torque)
+    }
+
+    pub fn asymmetric(
+        max_force_positive: Vec3,
+        max_force_negative: Vec3,
+        max_torque_positive: Vec3,
+        max_torque_negative: Vec3,
+    ) -> Self {
+        Self {
+            max_force_positive,
+            max_force_negative,
+            max_torque_positive,
+            max_torque_negative,
+        }
+    }
+
+    // Per-axis force limit on whichever side of each axis the local vector points to
+    pub fn force_limit_towards(&self, local_direction: Vec3) -> Vec3 {
+        Vec3::select(
+            local_direction.cmpge(Vec3::ZERO),
+            self.max_force_positive,
+            self.max_force_negative,
+        )
+    }
+
+    pub fn torque_limit_towards(&self, local_direction: Vec3) -> Vec3 {
+        Vec3::select(
+            local_direction.cmpge(Vec3::ZERO),
+            self.max_torque_positive,
+            self.max_torque_negative,
+        )
+    }
+
+    pub fn clamp_force(&self, local_force: Vec3) -> Vec3 {
+        local_force.clamp(-self.max_force_negative, self.max_force_positive)
+    }
+
+    pub fn clamp_torque(&self, local_torque: Vec3) -> Vec3 {
+        local_torque.clamp(-self.max_torque_negative, self.max_torque_positive)
+    }
+
+    // Whether a local force and torque are inside the limits, give or take rounding
+    pub fn contains(&self, local_force: Vec3, local_torque: Vec3) -> bool {
+        let tolerance = 1.0 + 1.0e-4;
+        local_force
+            .abs()
+            .cmple(self.force_limit_towards(local_force) * tolerance)
+            .all()
+            && local_torque
+                .abs()
+                .cmple(self.torque_limit_towards(local_torque) * tolerance)
+                .all()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SaturationMode {
+    // Clamp each local axis on its own, which bends diagonal commands towards axes with headroom
+    PerAxis,
+    // Scale force and torque by the same factor until they fit, keeping the whole wrench's
+    // direction
+    Uniform,
+    // Scale force and torque separately, each keeping its direction. Rotation claims its share
+    // first: at 0 this is the same as Uniform, at 1 rotation gets all it asks for before
+    // translation gets what is left.
+    RotationPriority { rotation_priority: f32 },
+}
+
+// How thruster_system fits commands that ask for more than the thrusters have, and what it
+// had to do last frame
+pub struct ThrustSaturation {
+    pub mode: SaturationMode,
+    pub saturated: bool,
+    // Factors the commanded force and torque were scaled by
+    pub linear_scale: f32,
+    pub angular_scale: f32,
+    // Share of the ship's linear authority left over once rotation had its share, 1 when
+    // rotation took nothing from translation
+    pub linear_authority: f32,
+}
+
+impl ThrustSaturation {
+    pub fn new(mode: SaturationMode) -> Self {
         Self {
-            max_force,
-            max_torque,
+            mode,
+            saturated: false,
+            linear_scale: 1.0,
+            angular_scale: 1.0,
+            linear_authority: 1.0,
+        }
+    }
+}
+
+// A single engine or RCS nozzle. The direction is the way it pushes the ship, opposite to its
+// exhaust, and both are in the ship's local frame with the position measured from the centre
+// of mass.
+#[derive(Clone)]
+pub struct Thruster {
+    pub local_position: Vec3,
+    pub local_direction: Vec3,
+    pub max_thrust: f32,
+    // Time constant of the throttle lag, zero responds instantly
+    pub response_time: f32,
+    pub throttle: f32,
+}
+
+impl Thruster {
+    pub fn new(
+        local_position: Vec3,
+        local_direction: Vec3,
+        max_thrust: f32,
+        response_time: f32,
+    ) -> Self {
+        Self {
+            local_position,
+            local_direction: local_direction.normalize(),
+            max_thrust,
+            response_time,
+            throttle: 0.0,
+        }
+    }
+
+    // Force and torque about the centre of mass at full throttle
+    pub fn max_force(&self) -> Vec3 {
+        self.local_direction * self.max_thrust
+    }
+
+    pub fn max_torque(&self) -> Vec3 {
+        self.local_position.cross(self.max_force())
+    }
+
+    // Moves the throttle towards its setpoint with a first order lag
+    pub fn update_throttle(&mut self, setpoint: f32, dt: f32) {
+        let setpoint = setpoint.clamp(0.0, 1.0);
+        if self.response_time <= 0.0 {
+            self.throttle = setpoint;
+        } else {
+            self.throttle += (setpoint - self.throttle) * (1.0 - (-dt / self.response_time).exp());
+        }
+    }
+}
+
+// hecs allows one component of a type per entity, so a ship's thrusters live together
+#[derive(Clone)]
+pub struct ThrusterArray {
+    pub thrusters: Vec<Thruster>,
+}
+
+impl ThrusterArray {
+    pub fn new(thrusters: Vec<Thruster>) -> Self {
+        Self { thrusters }
+    }
+
+    // RCS quads with nozzles along every axis at the four horizontal corners of a hull, plus a
+    // main engine at the back and a retro engine at the front
+    pub fn rcs_layout(
+        half_extents: Vec3,
+        rcs_thrust: f32,
+        main_thrust: f32,
+        retro_thrust: f32,
+    ) -> Self {
+        let mut thrusters = Vec::new();
+
+        for x in [-half_extents.x, half_extents.x] {
+            for z in [-half_extents.z, half_extents.z] {
+                for direction in [
+                    Vec3::X,
+                    Vec3::NEG_X,
+                    Vec3::Y,
+                    Vec3::NEG_Y,
+                    Vec3::Z,
+                    Vec3::NEG_Z,
+                ] {
+                    thrusters.push(Thruster::new(
+                        Vec3::new(x, 0.0, z),
+                        direction,
+                        rcs_thrust,
+                        0.05,
+                    ));
+                }
+            }
         }
+
+        thrusters.push(Thruster::new(
+            Vec3::new(0.0, 0.0, -half_extents.z),
+            Vec3::Z,
+            main_thrust,
+            0.5,
+        ));
+        thrusters.push(Thruster::new(
+            Vec3::new(0.0, 0.0, half_extents.z),
+            Vec3::NEG_Z,
+            retro_thrust,
+            0.5,
+        ));
+
+        Self::new(thrusters)
+    }
+
+    // Per-axis limits for navigation, each direction summed over every thruster that helps.
+    // These are optimistic since they ignore the force that comes with a torque and the
+    // reverse.
+    pub fn limits(&self) -> ThrusterLimits {
+        let axis_limit = |component: fn(&Thruster) -> Vec3, sign: f32| {
+            self.thrusters
+                .iter()
+                .map(|thruster| (component(thruster) * sign).max(Vec3::ZERO))
+                .fold(Vec3::ZERO, |sum, part| sum + part)
+        };
+
+        ThrusterLimits::asymmetric(
+            axis_limit(Thruster::max_force, 1.0),
+            axis_limit(Thruster::max_force, -1.0),
+            axis_limit(Thruster::max_torque, 1.0),
+            axis_limit(Thruster::max_torque, -1.0),
+        )
     }
 }
 
@@ -43,31 +264,144 @@ impl AccelerationControlCommand {
     }
 }
 
-pub struct FlightController {
-    // Consider separating tuning constants to separate shared struct and only keep per-ship state variables (error)
-    // Linear gains
-    pub lin_vel_kp: f32,
-    pub lin_vel_kd: f32,
-    pub lin_vel_prev: Vec3,
+// Tuning for a ship's velocity loops, shared by every ship of a class
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FlightControllerGains {
+    pub linear: PidGains,
+    pub angular: PidGains,
+}
+
+impl FlightControllerGains {
+    pub fn new(linear: PidGains, angular: PidGains) -> Self {
+        Self { linear, angular }
+    }
+}
 
-    // Angular gains
-    pub ang_vel_kp: f32,
-    pub ang_vel_kd: f32,
-    pub ang_vel_prev: Vec3,
+// PID loops turning velocity error into commanded acceleration
+pub struct FlightController {
+    pub gains: FlightControllerGains,
+    pub linear: PidState,
+    pub angular: PidState,
 }
 
 impl FlightController {
-    pub fn new(lin_vel_kp: f32, lin_vel_kd: f32, ang_vel_kp: f32, ang_vel_kd: f32) -> Self {
+    pub fn new(gains: FlightControllerGains) -> Self {
         Self {
-            lin_vel_kp,
+            gains,
+            linear: PidState::ZERO,
+            angular: PidState::ZERO,
+        }
+    }
 
-            lin_vel_kd,
-            ang_vel_kp,
+    // Swaps in new gains without a jump in the commanded acceleration
+    pub fn set_gains(&mut self, gains: FlightControllerGains) {
+        self.linear
+            .transfer_gains(&self.gains.linear, &gains.linear);
+        self.angular
+            .transfer_gains(&self.gains.angular, &gains.angular);
+        self.gains = gains;
+    }
+}
 
-            ang_vel_kd,
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FlightAssistMode {
+    // Input moves the NavigationTarget and the ship holds wherever it is left
+    PositionHold,
+    // Input sets the velocity the ship flies at, letting go brings it to a stop
+    Coupled,
+    // Input fires the thrusters straight, letting go leaves the ship coasting
+    Decoupled,
+    // Forward and back move the throttle, which holds a speed along the nose, the rest strafes
+    Cruise,
+}
 
-            lin_vel_prev: Vec3::ZERO,
-            ang_vel_prev: Vec3::ZERO,
+impl FlightAssistMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::PositionHold => Self::Coupled,
+            Self::Coupled => Self::Decoupled,
+            Self::Decoupled => Self::Cruise,
+            Self::Cruise => Self::PositionHold,
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PositionHold => "position hold",
+            Self::Coupled => "coupled",
+            Self::Decoupled => "decoupled",
+            Self::Cruise => "cruise",
+        }
+    }
+}
+
+// How a piloted ship turns PilotInput into flight, applied by flight_assist_system
+pub struct FlightAssist {
+    pub mode: FlightAssistMode,
+    // Turn input sets how fast the ship rotates, instead of turning the attitude it holds
+    pub rate_control: bool,
+    // How fast full input moves the held position, and the velocity it asks for when coupled
+    pub target_move_speed: f32,
+    pub max_speed: f32,
+    // Rotation rate at full turn input, in rad/s
+    pub max_turn_rate: f32,
+    // Cruise speed along the nose as a fraction of the max speed, negative for reverse
+    pub throttle: f32,
+    // Sideways speed at full input while cruising
+    pub strafe_speed: f32,
+    // Ship whose speed the throttle follows, until the pilot moves it
+    pub match_target: Option<Entity>,
+}
+
+impl FlightAssist {
+    pub fn new() -> Self {
+        Self {
+            mode: FlightAssistMode::PositionHold,
+            rate_control: false,
+            target_move_speed: 12.0,
+            max_speed: 50.0,
+            max_turn_rate: 0.6,
+            throttle: 0.0,
+            strafe_speed: 20.0,
+            match_target: None,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: FlightAssistMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    // Sets the throttle to hold a speed along the nose, as far as the max speed allows
+    pub fn set_cruise_speed(&mut self, speed: f32) {
+        self.throttle = (speed / self.max_speed).clamp(-1.0, 1.0);
+    }
+
+    pub fn cruise_speed(&self) -> f32 {
+        self.throttle * self.max_speed
+    }
+
+    // Whether the pilot flies the ship's translation, rather than navigation_system
+    pub fn is_manual(&self) -> bool {
+        self.mode != FlightAssistMode::PositionHold
+    }
+}
+
+// Written by the input code each frame, in the ship's local frame. Every axis runs from -1 to
+// 1, movement along the ship's axes and turning about them.
+pub struct PilotInput {
+    pub local_move: Vec3,
+    pub local_turn: Vec3,
+}
+
+impl PilotInput {
+    pub const ZERO: Self = Self {
+        local_move: Vec3::ZERO,
+        local_turn: Vec3::ZERO,
+    };
 }
@@ -1,54 +1,67 @@
 use hecs::World;
 
 use crate::{
-    flight::flight_components::{AccelerationControlCommand, FlightController, TargetVelocity},
+    flight::flight_components::{
+        AccelerationControlCommand, FlightAssist, FlightAssistMode, FlightController,
+        TargetVelocity, ThrustSaturation,
+    },
     physics::physics_components::Velocity,
 };
 
 pub fn flight_controller_system(world: &mut World, dt: f32) {
-    for (_entity, (velocity, target, controller, command)) in world
+    for (_entity, (velocity, target, controller, command, assist, saturation)) in world
         .query::<(
             &Velocity,
             &TargetVelocity,
             &mut FlightController,
             &mut AccelerationControlCommand,
+            Option<&FlightAssist>,
+            Option<&ThrustSaturation>,
         )>()
         .iter()
     {
-        // ----- Linear -----
-        let lin_vel_error = target.target_linear_velocity - velocity.linear;
-
-        let lin_vel_derivative = -(velocity.linear - controller.lin_vel_prev) / dt;
-        controller.lin_vel_prev = lin_vel_error;
+        let FlightController {
+            gains,
+            linear,
+            angular,
+        } = controller;
 
-        let linear_acceleration =
-            lin_vel_error * controller.lin_vel_kp + lin_vel_derivative * controller.lin_vel_kd;
+        // thruster_system scales the commands after this runs, so the loops learn how much got
+        // through a step late
+        let (linear_scale, angular_scale) = saturation.map_or((1.0, 1.0), |saturation| {
+            (saturation.linear_scale, saturation.angular_scale)
+        });
 
-        command.linear_acceleration = linear_acceleration;
+        // ----- Linear -----
+        // Decoupled ships take the pilot's acceleration as it is, with nothing damping the
+        // drift. The loop starts afresh when the assist comes back on.
+        if assist.is_some_and(|assist| assist.mode == FlightAssistMode::Decoupled) {
+            linear.reset();
+        } else {
+            command.linear_acceleration = linear.update(
+                &gains.linear,
+                target.target_linear_velocity,
+                velocity.linear,
+                dt,
+                linear_scale,
+            );
+        }
 
         // ----- Angular -----
-        let ang_vel_error = target.target_angular_velocity - velocity.angular;
-
-        let ang_vel_derivative = (ang_vel_error - controller.ang_vel_prev) / dt;
-        controller.ang_vel_prev = ang_vel_error;
-
-        let angular_acceleration =
-            ang_vel_error * controller.ang_vel_kp + ang_vel_derivative * controller.ang_vel_kd;
-
-        command.angular_acceleration = angular_acceleration;
-
-        // println!(
-        //     "P: {:.2}     |     D: {:.2}",
-        //     ang_vel_error * controller.ang_vel_kp,
-        //     ang_vel_derivative * controller.ang_vel_kd
-        // );
+        command.angular_acceleration = angular.update(
+            &gains.angular,
+            target.target_angular_velocity,
+            velocity.angular,
+            dt,
+            angular_scale,
+        );
 
         // println!(
         //     "<{:>07.2} {:>07.2}>         |         <{:>07.2} {:>07.2}>",
         //     target.target_linear_velocity,
-        //     linear_acceleration,
+        //     command.linear_acceleration,
         //     target.target_angular_velocity,
-        //     angular_acceleration
+        //     command.angular_acceleration
         // );
     }
 }
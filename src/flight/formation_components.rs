@@ -0,0 +1,73 @@
+use glam::Vec3;
+use hecs::Entity;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormationShape {
+    // Side by side with the leader, alternating right and left
+    LineAbreast { spacing: f32 },
+    // Trailing back from the leader on both sides
+    Wedge { spacing: f32 },
+    // Spread evenly over a sphere around the leader
+    SphereShell { radius: f32 },
+    // Fixed slots, members beyond the last one get none
+    Custom(Vec<Vec3>),
+}
+
+impl FormationShape {
+    // Slot offsets in the leader's frame for this many members, forward being +Z
+    pub fn slots(&self, count: usize) -> Vec<Vec3> {
+        match self {
+            Self::LineAbreast { spacing } => (0..count)
+                .map(|index| Vec3::X * side_rank(index) * *spacing)
+                .collect(),
+            Self::Wedge { spacing } => (0..count)
+                .map(|index| {
+                    let rank = side_rank(index);
+                    Vec3::new(rank, 0.0, -rank.abs()) * *spacing
+                })
+                .collect(),
+            Self::SphereShell { radius } => {
+                // Fibonacci lattice, which spreads any number of points about evenly
+                let golden_angle = std::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+                (0..count)
+                    .map(|index| {
+                        let y = 1.0 - 2.0 * (index as f32 + 0.5) / count as f32;
+                        let ring = (1.0 - y * y).sqrt();
+                        let angle = golden_angle * index as f32;
+                        Vec3::new(ring * angle.cos(), y, ring * angle.sin()) * *radius
+                    })
+                    .collect()
+            }
+            Self::Custom(slots) => slots.iter().copied().take(count).collect(),
+        }
+    }
+}
+
+// 1, -1, 2, -2 and so on, right then left, further out each pair
+fn side_rank(index: usize) -> f32 {
+    let rank = (index / 2 + 1) as f32;
+    if index.is_multiple_of(2) { rank } else { -rank }
+}
+
+// Lives on the leader. Members hold their slots through a rendezvous Pursuit of the leader,
+// kept up to date by formation_system.
+pub struct Formation {
+    pub shape: FormationShape,
+    // Members in slot order, member i flies slot i and any past the last slot hold position
+    pub members: Vec<Entity>,
+    // Offsets handed out at the last assignment
+    pub slots: Vec<Vec3>,
+    // Set when the members changed and the slots have to be handed out again
+    pub needs_assignment: bool,
+}
+
+impl Formation {
+    pub fn new(shape: FormationShape, members: Vec<Entity>) -> Self {
+        Self {
+            shape,
+            members,
+            slots: Vec::new(),
+            needs_assignment: true,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use glam::Vec3;
+use hecs::{Entity, World};
+
+use crate::{
+    flight::{
+        formation_components::Formation,
+        navigation_components::{Pursuit, PursuitLaw},
+    },
+    physics::transform::Transform,
+};
+
+// Keeps each formation member flying a rendezvous with its slot on the leader. Lost members
+// are dropped and the slots handed out again by assign_slots. Its assignment is only locally
+// optimal (no pairwise swap improves it), but that is enough to stop two members trading
+// places through each other while the gap closes up. Must run before pursuit_system.
+pub fn formation_system(world: &mut World) {
+    let mut orders: Vec<(Entity, Option<Pursuit>)> = Vec::new();
+
+    for (leader, formation) in world.query::<&mut Formation>().iter() {
+        let members = formation.members.len();
+        formation
+            .members
+            .retain(|&member| member != leader && world.contains(member));
+        if formation.members.len() != members {
+            formation.needs_assignment = true;
+        }
+
+        if formation.needs_assignment {
+            let Ok(leader_transform) = world.get::<&Transform>(leader) else {
+                continue;
+            };
+            let to_leader_frame = leader_transform.orientation.inverse();
+
+            // Members without a transform yet count as sitting on the leader
+            let positions: Vec<Vec3> = formation
+                .members
+                .iter()
+                .map(|&member| {
+                    world
+                        .get::<&Transform>(member)
+                        .map_or(Vec3::ZERO, |transform| {
+                            to_leader_frame * (transform.position - leader_transform.position)
+                        })
+                })
+                .collect();
+
+            formation.slots = formation.shape.slots(formation.members.len());
+            let assignment = assign_slots(&positions, &formation.slots);
+
+            let mut members: Vec<(usize, Entity)> = assignment
+                .into_iter()
+                .zip(formation.members.iter().copied())
+                .collect();
+            members.sort_by_key(|(slot, _member)| *slot);
+            formation.members = members.into_iter().map(|(_slot, member)| member).collect();
+            formation.needs_assignment = false;
+        }
+
+        for (index, &member) in formation.members.iter().enumerate() {
+            let order = formation
+                .slots
+                .get(index)
+                .map(|&local_offset| Pursuit::new(leader, PursuitLaw::Rendezvous { local_offset }));
+            orders.push((member, order));
+        }
+    }
+
+    for (member, order) in orders {
+        match order {
+            Some(order) => {
+                if let Ok(mut pursuit) = world.get::<&mut Pursuit>(member) {
+                    pursuit.target = order.target;
+                    pursuit.law = order.law;
+                    continue;
+                }
+                world
+                    .insert_one(member, order)
+                    .expect("Member should exist");
+            }
+            None => {
+                let _ = world.remove_one::<Pursuit>(member);
+            }
+        }
+    }
+}
+
+// Slot for each member, starting from the order they're in and swapping pairs while that
+// shortens the total squared distance, so the result is locally optimal (no pairwise swap
+// improves it) rather than the least total. Indices past the last slot mean no slot, which
+// costs nothing.
+fn assign_slots(positions: &[Vec3], slots: &[Vec3]) -> Vec<usize> {
+    let cost = |member: usize, slot: usize| {
+        slots
+            .get(slot)
+            .map_or(0.0, |&offset| positions[member].distance_squared(offset))
+    };
+
+    let mut assignment: Vec<usize> = (0..positions.len()).collect();
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for first in 0..assignment.len() {
+            for second in first + 1..assignment.len() {
+                let (a, b) = (assignment[first], assignment[second]);
+                let current = cost(first, a) + cost(second, b);
+                let swapped = cost(first, b) + cost(second, a);
+                if swapped < current - 1.0e-3 {
+                    assignment.swap(first, second);
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    assignment
+}
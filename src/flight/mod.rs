@@ -1,5 +1,20 @@
+pub mod avoidance_system;
+pub mod docking_components;
+pub mod docking_system;
+pub mod flight_assist_system;
 pub mod flight_components;
 pub mod flight_controller_system;
+pub mod formation_components;
+pub mod formation_system;
 pub mod navigation_components;
 pub mod navigation_system;
+pub mod orca;
+pub mod path_planning;
+pub mod path_planning_system;
+pub mod pid;
+pub mod pursuit_system;
+pub mod ship_class;
+pub mod thrust_allocation;
+pub mod thrust_saturation;
 pub mod thruster_system;
+pub mod waypoint_system;
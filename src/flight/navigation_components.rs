@@ -1,10 +1,28 @@
 use glam::{Quat, Vec3};
+use hecs::Entity;
 use std::collections::VecDeque;
 
+// How much further than its tolerances an arrived ship has to stray before it counts as
+// departed, so it doesn't flicker in and out at the edge
+pub const DEPARTURE_HYSTERESIS: f32 = 2.0;
+
+#[derive(Copy, Clone)]
 pub struct NavigationTarget {
     pub target_position: Vec3,
     pub target_orientation: Quat,
+    // Tolerances a ship has to be inside, all at once, to have arrived
     pub arrival_threshold: f32,
+    pub angle_tolerance: f32,
+    pub speed_tolerance: f32,
+    // Speed to fly through the target at instead of stopping there, for waypoints along a route
+    pub pass_through_speed: Option<f32>,
+    // How fast the target itself is moving. Ships plan their approach relative to it and arrive
+    // moving with it.
+    pub target_velocity: Vec3,
+    // How fast the target orientation is turning, so ships keep up with a spinning target
+    pub target_angular_velocity: Vec3,
+    // Cap on the speed the ship approaches the target at, relative to the target
+    pub max_speed: Option<f32>,
 }
 
 impl NavigationTarget {
@@ -13,26 +31,202 @@ impl NavigationTarget {
             target_position: position,
             target_orientation: orientation,
             arrival_threshold: threshold,
+            angle_tolerance: 5.0_f32.to_radians(),
+            speed_tolerance: 0.5,
+            pass_through_speed: None,
+            target_velocity: Vec3::ZERO,
+            target_angular_velocity: Vec3::ZERO,
+            max_speed: None,
+        }
+    }
+
+    pub fn with_angle_tolerance(mut self, angle_tolerance: f32) -> Self {
+        self.angle_tolerance = angle_tolerance;
+        self
+    }
+
+    pub fn with_speed_tolerance(mut self, speed_tolerance: f32) -> Self {
+        self.speed_tolerance = speed_tolerance;
+        self
+    }
+
+    pub fn with_pass_through_speed(mut self, speed: f32) -> Self {
+        self.pass_through_speed = Some(speed);
+        self
+    }
+
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = Some(max_speed);
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PursuitLaw {
+    // Fly straight at where the target is now, ending up in a tail chase
+    Pure,
+    // Fly at where the target will be by the time the ship gets there
+    Lead,
+    // Turn the flight path in proportion to how fast the line of sight to the target turns,
+    // times the navigation constant, usually between 3 and 5
+    ProportionalNavigation { navigation_constant: f32 },
+    // Come to rest at an offset in the target's frame, matching its velocity and orientation
+    Rendezvous { local_offset: Vec3 },
+}
+
+// Steers the ship's NavigationTarget after another entity, updated by pursuit_system
+pub struct Pursuit {
+    pub target: Entity,
+    pub law: PursuitLaw,
+    // Speed the intercepting laws close in on the target at, rendezvous stops instead
+    pub closing_speed: f32,
+}
+
+impl Pursuit {
+    pub fn new(target: Entity, law: PursuitLaw) -> Self {
+        Self {
+            target,
+            law,
+            closing_speed: 20.0,
+        }
+    }
+
+    pub fn with_closing_speed(mut self, closing_speed: f32) -> Self {
+        self.closing_speed = closing_speed;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RouteMode {
+    // Fly the route once and hold at the last waypoint
+    Once,
+    // Go back to the first waypoint after the last
+    Loop,
+    // Fly the route back and forth
+    PingPong,
+}
+
+// Whether a ship is at its NavigationTarget. Arrived ships hold station there until they stray
+// past the departure hysteresis. Added by navigation_system.
+pub struct NavigationStatus {
+    pub arrived: bool,
+    // Where the target was last frame
+    pub target_position: Vec3,
+}
+
+impl NavigationStatus {
+    pub fn new(target_position: Vec3) -> Self {
+        Self {
+            arrived: false,
+            target_position,
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NavigationEvent {
+    Arrived(Entity),
+    Departed(Entity),
+}
+
+// A route for waypoint_system to feed into the ship's NavigationTarget, the head of the queue
+// is the waypoint being flown to
 pub struct NavigationQueue {
     pub waypoints: VecDeque<NavigationTarget>,
+    pub mode: RouteMode,
+    // Waypoints reached on the current leg of a ping-pong route, newest last
+    pub return_leg: Vec<NavigationTarget>,
 }
 
 impl NavigationQueue {
     pub fn new() -> Self {
         Self {
             waypoints: VecDeque::new(),
+            mode: RouteMode::Once,
+            return_leg: Vec::new(),
         }
     }
 
+    pub fn with_mode(mut self, mode: RouteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     pub fn add_waypoint(&mut self, waypoint: NavigationTarget) {
         self.waypoints.push_back(waypoint);
     }
 
-    pub fn clear(&mut self) {
-        self.waypoints.clear();
+    // Drops the waypoint at the head, queueing it up again as the mode asks
+    pub fn advance(&mut self) {
+        let Some(reached) = self.waypoints.pop_front() else {
+            return;
+        };
+
+        match self.mode {
+            RouteMode::Once => {}
+            RouteMode::Loop => self.waypoints.push_back(reached),
+            RouteMode::PingPong => {
+                self.return_leg.push(reached);
+
+                // Turn around at the end, without flying to the waypoint just reached again
+                if self.waypoints.is_empty() && self.return_leg.len() > 1 {
+                    let turning_point = self.return_leg.pop().expect("Return leg isn't empty");
+                    self.waypoints.extend(self.return_leg.drain(..).rev());
+                    self.return_leg.push(turning_point);
+                } else if self.waypoints.is_empty() {
+                    self.waypoints.extend(self.return_leg.drain(..));
+                }
+            }
+        }
     }
 }
+
+// Lets avoidance_system bend the ship's target velocity around nearby ships
+pub struct CollisionAvoidance {
+    // How far ahead, in seconds, collisions are looked for
+    pub time_horizon: f32,
+    pub neighbour_distance: f32,
+    pub max_neighbours: usize,
+    // Extra clearance on top of the hulls' bounding spheres
+    pub safety_margin: f32,
+}
+
+impl CollisionAvoidance {
+    pub fn new() -> Self {
+        Self {
+            time_horizon: 4.0,
+            neighbour_distance: 150.0,
+            max_neighbours: 10,
+            safety_margin: 1.0,
+        }
+    }
+}
+
+// Asks path_planning_system for a route to the goal around fixed obstacles, replacing the
+// ship's NavigationQueue. Removed once planned, which can take a few frames.
+pub struct PathRequest {
+    pub goal: NavigationTarget,
+    // Extra clearance kept from obstacles on top of the hull's bounding sphere
+    pub margin: f32,
+    // Speed the corners of the route are flown through at
+    pub corner_speed: f32,
+}
+
+impl PathRequest {
+    pub fn new(goal: NavigationTarget) -> Self {
+        Self {
+            goal,
+            margin: 5.0,
+            corner_speed: 5.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PathEvent {
+    Planned(Entity),
+    // No way round the obstacles was found, the ship flies as close as the search got and
+    // holds there
+    Unreachable(Entity),
+}
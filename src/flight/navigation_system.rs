@@ -1,39 +1,70 @@
 use glam::Vec3;
-use hecs::World;
+use hecs::{Entity, World};
 
 use crate::{
     flight::{
-        flight_components::{TargetVelocity, ThrusterLimits},
-        navigation_components::NavigationTarget,
+        flight_components::{FlightAssist, TargetVelocity, ThrustSaturation, ThrusterLimits},
+        navigation_components::{
+            DEPARTURE_HYSTERESIS, NavigationEvent, NavigationStatus, NavigationTarget,
+        },
     },
     physics::{
-        physics_components::{InertiaProperties, MassProperties},
+        physics_components::{InertiaProperties, MassProperties, Velocity},
         transform::Transform,
     },
 };
 
-pub fn navigation_system(world: &mut World) {
+// How quickly an arrived ship closes what is left of the distance while holding station, per
+// second
+const STATION_KEEPING_GAIN: f32 = 0.5;
+
+// Sets each ship's target velocity to fly it to its NavigationTarget, and works out whether it
+// has arrived. Returns the ships that arrived or departed this frame.
+pub fn navigation_system(world: &mut World) -> Vec<NavigationEvent> {
+    let mut events = Vec::new();
+    let mut new_statuses = Vec::new();
+
     for (
-        _entity,
+        entity,
         (
             transform,
+            velocity,
             nav_target,
             control_target,
             thruster_limits,
             mass_properties,
             inertia_properties,
+            saturation,
+            status,
+            assist,
         ),
     ) in world
         .query::<(
             &Transform,
+            &Velocity,
             &NavigationTarget,
             &mut TargetVelocity,
             &ThrusterLimits,
             &MassProperties,
             &InertiaProperties,
+            Option<&ThrustSaturation>,
+            Option<&mut NavigationStatus>,
+            Option<&FlightAssist>,
         )>()
         .iter()
     {
+        // A pilot flying the ship by hand leaves only its attitude to navigation
+        let manual = assist.is_some_and(FlightAssist::is_manual);
+
+        let arrived = if manual {
+            status.map(|status| status.arrived)
+        } else {
+            update_arrival(entity, transform, velocity, nav_target, status, &mut events)
+        };
+        if arrived.is_none() {
+            new_statuses.push((entity, nav_target.target_position));
+        }
+
         // Angular
         let mut orientation_error = nav_target.target_orientation * transform.orientation.inverse();
 
@@ -44,37 +75,134 @@ pub fn navigation_system(world: &mut World) {
 
         let (axis, angle) = orientation_error.to_axis_angle();
 
-        let alpha = inertia_properties.inverse_inertia * thruster_limits.max_torque;
+        // Slowing the turn down takes torque against the rotation axis
+        let local_axis = transform.orientation.inverse() * axis;
+        let alpha =
+            inertia_properties.inverse_inertia * thruster_limits.torque_limit_towards(-local_axis);
 
-        control_target.target_angular_velocity = Vec3::new(
-            (2.0 * alpha.x * angle).sqrt() * axis.x,
-            (2.0 * alpha.y * angle).sqrt() * axis.y,
-            (2.0 * alpha.z * angle).sqrt() * axis.z,
-        );
+        control_target.target_angular_velocity = nav_target.target_angular_velocity
+            + Vec3::new(
+                (2.0 * alpha.x * angle).sqrt() * axis.x,
+                (2.0 * alpha.y * angle).sqrt() * axis.y,
+                (2.0 * alpha.z * angle).sqrt() * axis.z,
+            );
 
         // println!("{}", control_target.target_angular_velocity);
 
-        // Linear
+        if manual {
+            continue;
+        }
+
+        // Linear, planned relative to the target so moving targets are met at their speed
         let to_target = nav_target.target_position - transform.position;
         let distance = to_target.length();
+        let relative_velocity = velocity.linear - nav_target.target_velocity;
 
         if distance < nav_target.arrival_threshold {
-            control_target.target_linear_velocity = Vec3::ZERO;
+            control_target.target_linear_velocity = nav_target.target_velocity
+                + match nav_target.pass_through_speed {
+                    // Keep going until the route moves the target on
+                    Some(speed) => relative_velocity.normalize_or_zero() * speed,
+                    // Holding station only needs small corrections, where the braking curve
+                    // below would keep overshooting a target this close
+                    None if arrived == Some(true) => to_target * STATION_KEEPING_GAIN,
+                    None => Vec3::ZERO,
+                };
+            continue;
+        }
+
+        // Drifted out but not yet past the hysteresis, ease back in rather than racing back
+        if arrived == Some(true) && nav_target.pass_through_speed.is_none() {
+            control_target.target_linear_velocity =
+                nav_target.target_velocity + to_target * STATION_KEEPING_GAIN;
             continue;
         }
 
         let direction = to_target.normalize();
 
-        let max_acceleration = calculate_max_acceleration_in_direction(
-            direction,
+        // The approach speed is limited by how hard the ship can brake, which is thrust pointing
+        // back the way it came
+        let mut max_acceleration = calculate_max_acceleration_in_direction(
+            -direction,
             transform,
             thruster_limits,
             mass_properties.mass,
         );
 
+        // While rotation is eating into the thrust budget, plan to brake with what is left
+        if let Some(saturation) = saturation
+            && saturation.saturated
+        {
+            max_acceleration *= saturation.linear_authority;
+        }
+
+        // Pass-through targets only need braking down to the speed they're flown through at
+        let arrival_speed = nav_target.pass_through_speed.unwrap_or(0.0);
+        let mut approach_speed =
+            (arrival_speed * arrival_speed + 2.0 * max_acceleration * distance).sqrt();
+        if let Some(max_speed) = nav_target.max_speed {
+            approach_speed = approach_speed.min(max_speed);
+        }
+
         control_target.target_linear_velocity =
-            direction * (2.0 * max_acceleration * distance).sqrt();
+            nav_target.target_velocity + direction * approach_speed;
+    }
+
+    for (entity, target_position) in new_statuses {
+        world
+            .insert_one(entity, NavigationStatus::new(target_position))
+            .expect("Ship should exist");
     }
+
+    events
+}
+
+// Arrival needs the ship inside every tolerance at once, or just inside the threshold for
+// pass-through targets. Departure needs it to stray past one of them by the hysteresis.
+// Returns whether the ship is now arrived, None if it has no status yet.
+fn update_arrival(
+    entity: Entity,
+    transform: &Transform,
+    velocity: &Velocity,
+    nav_target: &NavigationTarget,
+    status: Option<&mut NavigationStatus>,
+    events: &mut Vec<NavigationEvent>,
+) -> Option<bool> {
+    let status = status?;
+
+    let distance = transform.position.distance(nav_target.target_position);
+    let angle = transform
+        .orientation
+        .angle_between(nav_target.target_orientation);
+    let speed = (velocity.linear - nav_target.target_velocity).length();
+
+    // Pass-through targets only care where the ship is
+    let holds_pose = nav_target.pass_through_speed.is_none();
+
+    // A target that jumps, like a route moving on to its next waypoint, leaves the ship behind
+    let retargeted =
+        nav_target.target_position.distance(status.target_position) > nav_target.arrival_threshold;
+    status.target_position = nav_target.target_position;
+
+    if status.arrived {
+        let strayed = retargeted
+            || distance > nav_target.arrival_threshold * DEPARTURE_HYSTERESIS
+            || (holds_pose && angle > nav_target.angle_tolerance * DEPARTURE_HYSTERESIS);
+        if strayed {
+            status.arrived = false;
+            events.push(NavigationEvent::Departed(entity));
+        }
+    } else {
+        let inside = distance < nav_target.arrival_threshold
+            && (!holds_pose
+                || (angle < nav_target.angle_tolerance && speed < nav_target.speed_tolerance));
+        if inside {
+            status.arrived = true;
+            events.push(NavigationEvent::Arrived(entity));
+        }
+    }
+
+    Some(status.arrived)
 }
 
 fn calculate_max_acceleration_in_direction(
@@ -87,11 +215,27 @@ fn calculate_max_acceleration_in_direction(
     let local_direction = transform.orientation.inverse() * direction;
 
     // Scale the direction by the thruster limits to find the point on the ellipsoid
-    // The ellipsoid is defined by (x/a)² + (y/b)² + (z/c)² = 1
+    // The ellipsoid is defined by (x/a)² + (y/b)² + (z/c)² = 1, using the limit on
+    // whichever side of each axis the direction points to
     // The maximum force in direction d is at the point where the ellipsoid intersects
     // the ray from origin in direction d
-    let scaled = local_direction / thruster_limits.max_force;
-    let scale_factor = 1.0 / scaled.length();
+    // Axes the direction doesn't use can't limit it, and an axis it does use with no thrust
+    // that way means it can't be pushed along at all
+    let limits = thruster_limits.force_limit_towards(local_direction);
+    let scaled = Vec3::from_array(std::array::from_fn(|axis| {
+        if local_direction[axis] == 0.0 {
+            0.0
+        } else if limits[axis] <= 0.0 {
+            f32::INFINITY
+        } else {
+            local_direction[axis] / limits[axis]
+        }
+    }));
+    let scaled_length = scaled.length();
+    if scaled_length == 0.0 || scaled_length.is_infinite() {
+        return 0.0;
+    }
+    let scale_factor = 1.0 / scaled_length;
 
     let max_force_local = local_direction * scale_factor;
     let max_force_magnitude = max_force_local.length();
@@ -0,0 +1,307 @@
+use glam::Vec3;
+
+const EPSILON: f32 = 1.0e-5;
+
+// A half-space of allowed velocities, everything on the side the normal points to
+#[derive(Copy, Clone, Debug)]
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+struct Line {
+    point: Vec3,
+    direction: Vec3,
+}
+
+// Optimal reciprocal collision avoidance, after van den Berg et al. and the RVO2-3D library.
+// The plane of velocities that keeps a ship clear of one neighbour for the time horizon,
+// taking the given share of the avoiding, 0.5 when the neighbour does its half.
+pub fn orca_plane(
+    velocity: Vec3,
+    relative_position: Vec3,
+    relative_velocity: Vec3,
+    combined_radius: f32,
+    time_horizon: f32,
+    dt: f32,
+    responsibility: f32,
+) -> Plane {
+    let distance_squared = relative_position.length_squared();
+    let combined_radius_squared = combined_radius * combined_radius;
+
+    let (normal, u) = if distance_squared > combined_radius_squared {
+        let inverse_time_horizon = 1.0 / time_horizon;
+        let w = relative_velocity - relative_position * inverse_time_horizon;
+        let w_length_squared = w.length_squared();
+        let dot = w.dot(relative_position);
+
+        if dot < 0.0 && dot * dot > combined_radius_squared * w_length_squared {
+            // Closest to the rounded-off front of the velocity obstacle
+            let w_length = w_length_squared.sqrt();
+            let unit_w = w / w_length;
+            (
+                unit_w,
+                unit_w * (combined_radius * inverse_time_horizon - w_length),
+            )
+        } else {
+            // Closest to the side of the cone
+            let a = distance_squared;
+            let b = relative_position.dot(relative_velocity);
+            let c = relative_velocity.length_squared()
+                - relative_position.cross(relative_velocity).length_squared()
+                    / (distance_squared - combined_radius_squared);
+            let t = (b + (b * b - a * c).max(0.0).sqrt()) / a;
+            let ww = relative_velocity - relative_position * t;
+            let ww_length = ww.length();
+            let unit_ww = ww / ww_length.max(EPSILON);
+            (unit_ww, unit_ww * (combined_radius * t - ww_length))
+        }
+    } else {
+        // Already overlapping, get clear within the step
+        let inverse_dt = 1.0 / dt;
+        let w = relative_velocity - relative_position * inverse_dt;
+        let w_length = w.length();
+        let unit_w = w / w_length.max(EPSILON);
+        (unit_w, unit_w * (combined_radius * inverse_dt - w_length))
+    };
+
+    Plane {
+        point: velocity + u * responsibility,
+        normal,
+    }
+}
+
+// The velocity inside the sphere closest to the preferred one that satisfies every plane.
+// When they can't all be satisfied, the one that breaks the worst of them the least.
+pub fn solve_velocity(planes: &[Plane], center: Vec3, radius: f32, preferred: Vec3) -> Vec3 {
+    // The programs below expect the sphere around the origin
+    let planes: Vec<Plane> = planes
+        .iter()
+        .map(|plane| Plane {
+            point: plane.point - center,
+            normal: plane.normal,
+        })
+        .collect();
+
+    let mut result = Vec3::ZERO;
+    let failed = linear_program3(&planes, radius, preferred - center, false, &mut result);
+    if failed < planes.len() {
+        linear_program4(&planes, failed, radius, &mut result);
+    }
+
+    result + center
+}
+
+fn violates(plane: &Plane, velocity: Vec3) -> bool {
+    plane.normal.dot(plane.point - velocity) > 0.0
+}
+
+// Best point on a line, inside the sphere and the first plane_count planes
+fn linear_program1(
+    planes: &[Plane],
+    plane_count: usize,
+    line: &Line,
+    radius: f32,
+    optimal: Vec3,
+    optimise_direction: bool,
+    result: &mut Vec3,
+) -> bool {
+    let dot = line.point.dot(line.direction);
+    let discriminant = dot * dot + radius * radius - line.point.length_squared();
+    if discriminant < 0.0 {
+        // The line misses the sphere
+        return false;
+    }
+
+    let root = discriminant.sqrt();
+    let mut t_left = -dot - root;
+    let mut t_right = -dot + root;
+
+    for plane in &planes[..plane_count] {
+        let numerator = (plane.point - line.point).dot(plane.normal);
+        let denominator = line.direction.dot(plane.normal);
+
+        if denominator * denominator <= EPSILON {
+            // Parallel to the plane, either all of the line is allowed or none of it
+            if numerator > 0.0 {
+                return false;
+            }
+            continue;
+        }
+
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_left = t_left.max(t);
+        } else {
+            t_right = t_right.min(t);
+        }
+
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if optimise_direction {
+        if optimal.dot(line.direction) > 0.0 {
+            t_right
+        } else {
+            t_left
+        }
+    } else {
+        line.direction
+            .dot(optimal - line.point)
+            .clamp(t_left, t_right)
+    };
+    *result = line.point + line.direction * t;
+    true
+}
+
+// Best point on one plane, inside the sphere and the planes before it
+fn linear_program2(
+    planes: &[Plane],
+    plane_index: usize,
+    radius: f32,
+    optimal: Vec3,
+    optimise_direction: bool,
+    result: &mut Vec3,
+) -> bool {
+    let plane = planes[plane_index];
+    let plane_distance = plane.point.dot(plane.normal);
+    let plane_distance_squared = plane_distance * plane_distance;
+    let radius_squared = radius * radius;
+
+    if plane_distance_squared > radius_squared {
+        // The plane misses the sphere
+        return false;
+    }
+
+    let plane_radius_squared = radius_squared - plane_distance_squared;
+    let plane_center = plane.normal * plane_distance;
+
+    if optimise_direction {
+        let in_plane = optimal - plane.normal * optimal.dot(plane.normal);
+        let in_plane_length_squared = in_plane.length_squared();
+        *result = if in_plane_length_squared <= EPSILON {
+            plane_center
+        } else {
+            plane_center + in_plane * (plane_radius_squared / in_plane_length_squared).sqrt()
+        };
+    } else {
+        *result = optimal + plane.normal * (plane.point - optimal).dot(plane.normal);
+        if result.length_squared() > radius_squared {
+            let from_center = *result - plane_center;
+            *result = plane_center
+                + from_center * (plane_radius_squared / from_center.length_squared()).sqrt();
+        }
+    }
+
+    for index in 0..plane_index {
+        let other = planes[index];
+        if !violates(&other, *result) {
+            continue;
+        }
+
+        let cross = other.normal.cross(plane.normal);
+        if cross.length_squared() <= EPSILON {
+            // Parallel planes facing apart leave nothing
+            return false;
+        }
+
+        let direction = cross.normalize();
+        let line_normal = direction.cross(plane.normal);
+        let line = Line {
+            point: plane.point
+                + line_normal
+                    * ((other.point - plane.point).dot(other.normal)
+                        / line_normal.dot(other.normal)),
+            direction,
+        };
+
+        if !linear_program1(
+            planes,
+            index,
+            &line,
+            radius,
+            optimal,
+            optimise_direction,
+            result,
+        ) {
+            return false;
+        }
+    }
+
+    true
+}
+
+// Best point in the sphere and all the planes. Returns the index of the plane it failed on,
+// or the number of planes when it didn't fail.
+fn linear_program3(
+    planes: &[Plane],
+    radius: f32,
+    optimal: Vec3,
+    optimise_direction: bool,
+    result: &mut Vec3,
+) -> usize {
+    *result = if optimise_direction {
+        optimal * radius
+    } else if optimal.length_squared() > radius * radius {
+        optimal.normalize() * radius
+    } else {
+        optimal
+    };
+
+    for index in 0..planes.len() {
+        if violates(&planes[index], *result) {
+            let previous = *result;
+            if !linear_program2(planes, index, radius, optimal, optimise_direction, result) {
+                *result = previous;
+                return index;
+            }
+        }
+    }
+
+    planes.len()
+}
+
+// Fallback when the planes leave no room, minimising how far the worst one is broken
+fn linear_program4(planes: &[Plane], first_failed: usize, radius: f32, result: &mut Vec3) {
+    let mut distance = 0.0;
+
+    for index in first_failed..planes.len() {
+        let plane = planes[index];
+        if plane.normal.dot(plane.point - *result) <= distance {
+            continue;
+        }
+
+        let mut projected = Vec::with_capacity(index);
+        for other in &planes[..index] {
+            let cross = other.normal.cross(plane.normal);
+            let point = if cross.length_squared() <= EPSILON {
+                if plane.normal.dot(other.normal) > 0.0 {
+                    // Same direction, the other plane adds nothing
+                    continue;
+                }
+                (plane.point + other.point) * 0.5
+            } else {
+                let line_normal = cross.cross(plane.normal);
+                plane.point
+                    + line_normal
+                        * ((other.point - plane.point).dot(other.normal)
+                            / line_normal.dot(other.normal))
+            };
+
+            projected.push(Plane {
+                point,
+                normal: (other.normal - plane.normal).normalize(),
+            });
+        }
+
+        let previous = *result;
+        if linear_program3(&projected, radius, plane.normal, true, result) < projected.len() {
+            // Only rounding can get here, keep what we had
+            *result = previous;
+        }
+
+        distance = plane.normal.dot(plane.point - *result);
+    }
+}
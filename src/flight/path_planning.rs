@@ -0,0 +1,296 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use glam::{IVec3, Vec3};
+use rapier3d::{parry::query::ShapeCastOptions, prelude::*};
+
+use crate::physics::physics_world::PhysicsWorld;
+
+// Voxels A* may look at in total before settling for the closest it got to a goal it can't reach
+const MAX_EXPANDED: usize = 20_000;
+
+// Smallest voxel, so tiny clearances don't blow up the search
+const MIN_VOXEL_SIZE: f32 = 1.0;
+
+struct OpenVoxel {
+    voxel: IVec3,
+    estimate: f32,
+}
+
+impl PartialEq for OpenVoxel {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenVoxel {}
+
+impl PartialOrd for OpenVoxel {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenVoxel {
+    // Reversed, so the heap pops the lowest estimate first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+// Waypoints after the start. When the goal can't be reached they lead as close to it as the
+// search got instead, and are empty if it got no closer than the start.
+pub struct PlannedPath {
+    pub waypoints: Vec<Vec3>,
+    pub reaches_goal: bool,
+}
+
+// Finds a way from start to goal that keeps a sphere of the clearance radius off every fixed
+// collider, by A* over a sparse voxel grid that is only tested against the colliders where the
+// search reaches. The grid path is then pulled tight so only the corners are left. The search
+// is carried on a few voxels at a time so it can be spread over several frames.
+pub struct PathSearch {
+    pub start: Vec3,
+    pub goal: Vec3,
+    pub clearance: f32,
+    voxel_size: f32,
+    goal_voxel: IVec3,
+    open: BinaryHeap<OpenVoxel>,
+    cost_so_far: HashMap<IVec3, f32>,
+    came_from: HashMap<IVec3, IVec3>,
+    // Whether each voxel tested so far keeps the clearance sphere off the obstacles
+    free: HashMap<IVec3, bool>,
+    closest: IVec3,
+    expanded: usize,
+    started: bool,
+}
+
+impl PathSearch {
+    pub fn new(start: Vec3, goal: Vec3, clearance: f32) -> Self {
+        let voxel_size = clearance.max(MIN_VOXEL_SIZE);
+        let goal_voxel = ((goal - start) / voxel_size).round().as_ivec3();
+
+        let mut search = Self {
+            start,
+            goal,
+            clearance,
+            voxel_size,
+            goal_voxel,
+            open: BinaryHeap::new(),
+            cost_so_far: HashMap::new(),
+            came_from: HashMap::new(),
+            free: HashMap::new(),
+            closest: IVec3::ZERO,
+            expanded: 0,
+            started: false,
+        };
+        search.open.push(OpenVoxel {
+            voxel: IVec3::ZERO,
+            estimate: search.heuristic(IVec3::ZERO),
+        });
+        search.cost_so_far.insert(IVec3::ZERO, 0.0);
+        search
+    }
+
+    // Expands up to `budget` more voxels. Returns the path once the goal is found or the search
+    // gives up, and None while it needs more calls. The query pipeline must be up to date.
+    pub fn advance(&mut self, physics_world: &PhysicsWorld, budget: usize) -> Option<PlannedPath> {
+        let planner = Planner {
+            physics_world,
+            clearance: self.clearance,
+        };
+
+        if !self.started {
+            self.started = true;
+
+            if planner.segment_clear(self.start, self.goal) {
+                return Some(PlannedPath {
+                    waypoints: vec![self.goal],
+                    reaches_goal: true,
+                });
+            }
+
+            // Goals inside an obstacle can never be reached, and searching for them would only
+            // give up after the whole budget
+            if !planner.point_clear(self.goal) {
+                return Some(PlannedPath {
+                    waypoints: Vec::new(),
+                    reaches_goal: false,
+                });
+            }
+        }
+
+        for _ in 0..budget {
+            let Some(OpenVoxel { voxel, estimate }) = self.open.pop() else {
+                return Some(self.finish(&planner, self.closest, false));
+            };
+            let cost = self.cost_so_far[&voxel];
+
+            // Stale entry for a voxel that has since been reached more cheaply
+            if estimate > cost + self.heuristic(voxel) + f32::EPSILON {
+                continue;
+            }
+
+            if voxel == self.goal_voxel {
+                return Some(self.finish(&planner, voxel, true));
+            }
+            if self.heuristic(voxel) < self.heuristic(self.closest) {
+                self.closest = voxel;
+            }
+
+            self.expanded += 1;
+            if self.expanded > MAX_EXPANDED {
+                return Some(self.finish(&planner, self.closest, false));
+            }
+
+            for offset in NEIGHBOURS {
+                let neighbour = voxel + offset;
+                let point = self.start + neighbour.as_vec3() * self.voxel_size;
+                let is_free = *self
+                    .free
+                    .entry(neighbour)
+                    .or_insert_with(|| planner.point_clear(point));
+                if !is_free {
+                    continue;
+                }
+
+                let neighbour_cost = cost + offset.as_vec3().length();
+                if self
+                    .cost_so_far
+                    .get(&neighbour)
+                    .is_some_and(|&known| known <= neighbour_cost)
+                {
+                    continue;
+                }
+
+                self.cost_so_far.insert(neighbour, neighbour_cost);
+                self.came_from.insert(neighbour, voxel);
+                self.open.push(OpenVoxel {
+                    voxel: neighbour,
+                    estimate: neighbour_cost + self.heuristic(neighbour),
+                });
+            }
+        }
+
+        None
+    }
+
+    fn heuristic(&self, voxel: IVec3) -> f32 {
+        (self.goal_voxel - voxel).as_vec3().length()
+    }
+
+    // Walks back from the end voxel to the start and pulls the route tight. When it doesn't
+    // reach the goal the route leads to the voxel nearest the goal the search got to.
+    fn finish(&self, planner: &Planner, end: IVec3, reaches_goal: bool) -> PlannedPath {
+        let mut voxels = vec![end];
+        let mut current = end;
+        while let Some(&previous) = self.came_from.get(&current) {
+            voxels.push(previous);
+            current = previous;
+        }
+        voxels.reverse();
+
+        let mut points: Vec<Vec3> = voxels
+            .iter()
+            .map(|voxel| self.start + voxel.as_vec3() * self.voxel_size)
+            .collect();
+        // The grid is anchored on the start, swap the end voxel for the exact goal
+        if reaches_goal {
+            points.pop();
+            points.push(self.goal);
+        }
+
+        PlannedPath {
+            waypoints: planner.smooth(&points),
+            reaches_goal,
+        }
+    }
+}
+
+struct Planner<'a> {
+    physics_world: &'a PhysicsWorld,
+    clearance: f32,
+}
+
+impl Planner<'_> {
+    // String pulling, from each corner skip ahead to the furthest point still in clear view
+    fn smooth(&self, points: &[Vec3]) -> Vec<Vec3> {
+        let mut waypoints = Vec::new();
+        let mut from = 0;
+
+        while from < points.len() - 1 {
+            let mut to = from + 1;
+            for candidate in (from + 2..points.len()).rev() {
+                if self.segment_clear(points[from], points[candidate]) {
+                    to = candidate;
+                    break;
+                }
+            }
+            waypoints.push(points[to]);
+            from = to;
+        }
+
+        waypoints
+    }
+
+    fn point_clear(&self, point: Vec3) -> bool {
+        self.physics_world
+            .query_pipeline
+            .intersection_with_shape(
+                &self.physics_world.bodies,
+                &self.physics_world.colliders,
+                &Isometry::translation(point.x, point.y, point.z),
+                &Ball::new(self.clearance),
+                QueryFilter::only_fixed(),
+            )
+            .is_none()
+    }
+
+    // Sweeps the clearance sphere along the segment. Starting out overlapping something only
+    // counts if the sweep goes further into it, so ships parked close to a station can leave.
+    fn segment_clear(&self, from: Vec3, to: Vec3) -> bool {
+        let travel = to - from;
+        let options = ShapeCastOptions {
+            max_time_of_impact: 1.0,
+            stop_at_penetration: false,
+            ..ShapeCastOptions::default()
+        };
+
+        self.physics_world
+            .query_pipeline
+            .cast_shape(
+                &self.physics_world.bodies,
+                &self.physics_world.colliders,
+                &Isometry::translation(from.x, from.y, from.z),
+                &vector![travel.x, travel.y, travel.z],
+                &Ball::new(self.clearance),
+                options,
+                QueryFilter::only_fixed(),
+            )
+            .is_none()
+    }
+}
+
+const NEIGHBOURS: [IVec3; 26] = {
+    let mut neighbours = [IVec3::ZERO; 26];
+    let mut index = 0;
+    let mut x = -1;
+    while x <= 1 {
+        let mut y = -1;
+        while y <= 1 {
+            let mut z = -1;
+            while z <= 1 {
+                if x != 0 || y != 0 || z != 0 {
+                    neighbours[index] = IVec3::new(x, y, z);
+                    index += 1;
+                }
+                z += 1;
+            }
+            y += 1;
+        }
+        x += 1;
+    }
+    neighbours
+};
@@ -0,0 +1,121 @@
+use glam::{Quat, Vec3};
+use hecs::{Entity, Without, World};
+
+use crate::{
+    flight::{
+        navigation_components::{NavigationQueue, NavigationTarget, PathEvent, PathRequest},
+        path_planning::{PathSearch, PlannedPath},
+    },
+    physics::{physics_components::BoxCollider, physics_world::PhysicsWorld, transform::Transform},
+};
+
+// Voxels A* expands per frame across every ship that is planning, so a hard route is spread
+// over several frames instead of stalling one
+const EXPANSIONS_PER_FRAME: usize = 1_000;
+
+// Turns each PathRequest into a NavigationQueue that flies the ship around fixed obstacles to
+// the goal, passing through the corners on the way. When the goal can't be reached the ship
+// flies as close as the search got and holds there instead. The search is kept on the ship as
+// a PathSearch until it finishes, and the ship carries on with what it was doing meanwhile.
+// Must run before waypoint_system.
+pub fn path_planning_system(world: &mut World, physics_world: &mut PhysicsWorld) -> Vec<PathEvent> {
+    // Searches whose request was taken away before they finished
+    let abandoned: Vec<Entity> = world
+        .query_mut::<Without<&PathSearch, &PathRequest>>()
+        .into_iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in abandoned {
+        world.remove_one::<PathSearch>(entity).ok();
+    }
+
+    let mut events = Vec::new();
+    if world.query_mut::<&PathRequest>().into_iter().len() == 0 {
+        return events;
+    }
+
+    // Start a search for new requests, and over again for ones whose goal has changed
+    let new_searches: Vec<(Entity, PathSearch)> = world
+        .query::<(&Transform, &BoxCollider, &PathRequest, Option<&PathSearch>)>()
+        .iter()
+        .filter_map(|(entity, (transform, collider, request, search))| {
+            // Bounding sphere of the hull, so the ship clears obstacles whichever way it faces
+            let radius = collider.extents.length() * 0.5 * transform.scale.max_element();
+            let clearance = radius + request.margin;
+            let goal = request.goal.target_position;
+            let current =
+                search.is_some_and(|search| search.goal == goal && search.clearance == clearance);
+            (!current).then(|| (entity, PathSearch::new(transform.position, goal, clearance)))
+        })
+        .collect();
+    for (entity, search) in new_searches {
+        world.insert_one(entity, search).expect("Ship should exist");
+    }
+
+    // Bodies spawned since the last step aren't in the query pipeline yet
+    physics_world
+        .query_pipeline
+        .update(&physics_world.colliders);
+
+    let mut searches = world.query::<(&PathRequest, &mut PathSearch)>();
+    let share = (EXPANSIONS_PER_FRAME / searches.iter().len().max(1)).max(1);
+    let finished: Vec<(Entity, Vec3, PlannedPath, NavigationTarget, f32, f32)> = searches
+        .iter()
+        .filter_map(|(entity, (request, search))| {
+            let path = search.advance(physics_world, share)?;
+            Some((
+                entity,
+                search.start,
+                path,
+                request.goal,
+                request.margin,
+                request.corner_speed,
+            ))
+        })
+        .collect();
+    drop(searches);
+
+    for (entity, start, path, goal, margin, corner_speed) in finished {
+        let (&last, corners) = path.waypoints.split_last().unwrap_or((&start, &[]));
+        let end = if path.reaches_goal {
+            events.push(PathEvent::Planned(entity));
+            goal
+        } else {
+            events.push(PathEvent::Unreachable(entity));
+            // Holds at the end of the partial route with the goal's tolerances, or where it is
+            // if the search got no closer
+            NavigationTarget {
+                target_position: last,
+                ..goal
+            }
+        };
+
+        let mut queue = NavigationQueue::new();
+        let mut previous = start;
+        for &corner in corners {
+            // Face along the leg so the main engine does the pushing
+            let orientation = (corner - previous)
+                .try_normalize()
+                .map_or(goal.target_orientation, |direction| {
+                    Quat::from_rotation_arc(Vec3::Z, direction)
+                });
+            // Turning for the next leg early cuts the corner by up to the threshold, which has
+            // to come out of the margin
+            queue.add_waypoint(
+                NavigationTarget::new(corner, orientation, margin * 0.5)
+                    .with_pass_through_speed(corner_speed),
+            );
+            previous = corner;
+        }
+        queue.add_waypoint(end);
+
+        // Start on the first leg straight away, for ships that weren't navigating before
+        let first = *queue.waypoints.front().expect("Route ends with the goal");
+        world.remove::<(PathRequest, PathSearch)>(entity).ok();
+        world
+            .insert(entity, (queue, first))
+            .expect("Ship should exist");
+    }
+
+    events
+}
@@ -0,0 +1,147 @@
+use glam::Vec3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AntiWindup {
+    // Stop integrating on an axis while the output is saturated and the error would push it
+    // further in
+    Clamping,
+    // Bleed the integral off by how far the output got clamped, with this time constant
+    BackCalculation { tracking_time: f32 },
+}
+
+// Tuning for one three-axis PID loop. Shared between ships of a class, the per-ship state
+// lives in PidState.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PidGains {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    // Time constant of the low-pass filter on the derivative, zero leaves it unfiltered
+    pub derivative_filter_time: f32,
+    // Largest length of the output vector, a rough bound when the thrusters' own envelope comes
+    // back through `output_scale`
+    pub output_limit: f32,
+    pub anti_windup: AntiWindup,
+}
+
+impl PidGains {
+    pub fn new(kp: f32, ki: f32, kd: f32) -> Self {
+        Self {
+            kp,
+            ki,
+            kd,
+            derivative_filter_time: 0.05,
+            output_limit: f32::INFINITY,
+            anti_windup: AntiWindup::Clamping,
+        }
+    }
+
+    pub fn with_output_limit(mut self, output_limit: f32) -> Self {
+        self.output_limit = output_limit;
+        self
+    }
+
+    pub fn with_derivative_filter(mut self, derivative_filter_time: f32) -> Self {
+        self.derivative_filter_time = derivative_filter_time;
+        self
+    }
+
+    pub fn with_anti_windup(mut self, anti_windup: AntiWindup) -> Self {
+        self.anti_windup = anti_windup;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PidState {
+    // The integral term itself rather than the integral of the error, so changing ki doesn't
+    // change the output
+    pub integral: Vec3,
+    pub filtered_derivative: Vec3,
+    pub prev_measurement: Option<Vec3>,
+    pub prev_error: Vec3,
+}
+
+impl PidState {
+    pub const ZERO: Self = Self {
+        integral: Vec3::ZERO,
+        filtered_derivative: Vec3::ZERO,
+        prev_measurement: None,
+        prev_error: Vec3::ZERO,
+    };
+
+    // One controller step. The derivative acts on the measurement rather than the error, so
+    // setpoint jumps don't kick the output. `output_scale` is the share of the output the
+    // thrusters actually delivered last step, as their saturation reported it, 1 when it all
+    // got through. Saturation scales the whole vector, so the output is treated as saturated
+    // as a whole too rather than axis by axis.
+    pub fn update(
+        &mut self,
+        gains: &PidGains,
+        setpoint: Vec3,
+        measurement: Vec3,
+        dt: f32,
+        output_scale: f32,
+    ) -> Vec3 {
+        let error = setpoint - measurement;
+
+        let raw_derivative = match self.prev_measurement {
+            Some(prev_measurement) if dt > 0.0 => -(measurement - prev_measurement) / dt,
+            _ => Vec3::ZERO,
+        };
+        let alpha = if gains.derivative_filter_time > 0.0 {
+            dt / (gains.derivative_filter_time + dt)
+        } else {
+            1.0
+        };
+        self.filtered_derivative += (raw_derivative - self.filtered_derivative) * alpha;
+        self.prev_measurement = Some(measurement);
+        self.prev_error = error;
+
+        let proportional = error * gains.kp;
+        let derivative = self.filtered_derivative * gains.kd;
+        let integral_step = error * gains.ki * dt;
+
+        let unclamped = proportional + self.integral + integral_step + derivative;
+        let output = unclamped.clamp_length_max(gains.output_limit);
+        // What the ship will most likely get, commands rarely change much from one step to
+        // the next
+        let applied = output * output_scale.clamp(0.0, 1.0);
+
+        match gains.anti_windup {
+            AntiWindup::Clamping => {
+                // Keep integrating while the output isn't saturated, or on axes where the
+                // error is pulling it back out of saturation
+                let saturated = applied != unclamped;
+                let winding_up = (error * unclamped).cmpgt(Vec3::ZERO);
+                if saturated {
+                    self.integral += Vec3::select(winding_up, Vec3::ZERO, integral_step);
+                } else {
+                    self.integral += integral_step;
+                }
+            }
+            AntiWindup::BackCalculation { tracking_time } => {
+                let correction = if tracking_time > 0.0 {
+                    (applied - unclamped) * (dt / tracking_time)
+                } else {
+                    applied - unclamped
+                };
+                self.integral += integral_step + correction;
+            }
+        }
+
+        output
+    }
+
+    // Moves the integral so the output stays where it was after the gains change, instead of
+    // jumping with the new proportional and derivative terms
+    pub fn transfer_gains(&mut self, old: &PidGains, new: &PidGains) {
+        let old_terms = self.prev_error * old.kp + self.filtered_derivative * old.kd;
+        let new_terms = self.prev_error * new.kp + self.filtered_derivative * new.kd;
+        self.integral += old_terms - new_terms;
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::ZERO;
+    }
+}
@@ -0,0 +1,170 @@
+use glam::{Quat, Vec3};
+use hecs::{Entity, World};
+
+use crate::{
+    flight::navigation_components::{NavigationTarget, Pursuit, PursuitLaw},
+    physics::{physics_components::Velocity, transform::Transform},
+};
+
+// Where a pursuer should be flying this frame
+struct Aim {
+    position: Vec3,
+    orientation: Option<Quat>,
+    velocity: Vec3,
+    angular_velocity: Vec3,
+    pass_through_speed: Option<f32>,
+}
+
+// Steers each pursuing ship's NavigationTarget after the entity it is chasing, by its
+// PursuitLaw. A ship whose target is gone stops at the last point it was aiming for. Must run
+// before navigation_system.
+pub fn pursuit_system(world: &mut World) {
+    let mut aims: Vec<(Entity, Option<Aim>)> = Vec::new();
+
+    for (entity, (transform, velocity, pursuit)) in world
+        .query::<(&Transform, &Velocity, &Pursuit)>()
+        .with::<&NavigationTarget>()
+        .iter()
+    {
+        let target = world
+            .query_one::<(&Transform, Option<&Velocity>)>(pursuit.target)
+            .ok()
+            .and_then(|mut query| {
+                query.get().map(|(target_transform, target_velocity)| {
+                    (
+                        target_transform.position,
+                        target_transform.orientation,
+                        target_velocity.map_or(Vec3::ZERO, |velocity| velocity.linear),
+                        target_velocity.map_or(Vec3::ZERO, |velocity| velocity.angular),
+                    )
+                })
+            });
+
+        let aim = target.map(
+            |(target_position, target_orientation, target_velocity, target_spin)| {
+                aim_at(
+                    transform,
+                    velocity,
+                    pursuit,
+                    target_position,
+                    target_orientation,
+                    target_velocity,
+                    target_spin,
+                )
+            },
+        );
+        aims.push((entity, aim));
+    }
+
+    for (entity, aim) in aims {
+        let Ok(mut nav_target) = world.get::<&mut NavigationTarget>(entity) else {
+            continue;
+        };
+
+        let Some(aim) = aim else {
+            nav_target.target_velocity = Vec3::ZERO;
+            nav_target.target_angular_velocity = Vec3::ZERO;
+            nav_target.pass_through_speed = None;
+            continue;
+        };
+
+        nav_target.target_position = aim.position;
+        if let Some(orientation) = aim.orientation {
+            nav_target.target_orientation = orientation;
+        }
+        nav_target.target_velocity = aim.velocity;
+        nav_target.target_angular_velocity = aim.angular_velocity;
+        nav_target.pass_through_speed = aim.pass_through_speed;
+    }
+}
+
+fn aim_at(
+    transform: &Transform,
+    velocity: &Velocity,
+    pursuit: &Pursuit,
+    target_position: Vec3,
+    target_orientation: Quat,
+    target_velocity: Vec3,
+    target_spin: Vec3,
+) -> Aim {
+    let to_target = target_position - transform.position;
+    let line_of_sight = to_target.normalize_or_zero();
+
+    let intercept = |position: Vec3| Aim {
+        position,
+        // Face where it's going so the main engine does the pushing
+        orientation: (position - transform.position)
+            .try_normalize()
+            .map(|direction| Quat::from_rotation_arc(Vec3::Z, direction)),
+        velocity: Vec3::ZERO,
+        angular_velocity: Vec3::ZERO,
+        pass_through_speed: Some(pursuit.closing_speed),
+    };
+
+    match pursuit.law {
+        PursuitLaw::Pure => intercept(target_position),
+        PursuitLaw::Lead => {
+            let speed = velocity.linear.length().max(pursuit.closing_speed);
+            let time = intercept_time(to_target, target_velocity, speed).unwrap_or(0.0);
+            intercept(target_position + target_velocity * time)
+        }
+        PursuitLaw::ProportionalNavigation {
+            navigation_constant,
+        } => {
+            // Line of sight rate from the target's motion across it, and the acceleration that
+            // turns the flight path with it
+            let relative_velocity = target_velocity - velocity.linear;
+            let range_squared = to_target.length_squared().max(f32::EPSILON);
+            let line_of_sight_rate = to_target.cross(relative_velocity) / range_squared;
+            let closing_speed = (-relative_velocity.dot(line_of_sight)).max(pursuit.closing_speed);
+            let acceleration =
+                navigation_constant * closing_speed * line_of_sight_rate.cross(line_of_sight);
+
+            // Aim where that acceleration takes the ship by the time it closes the range,
+            // keeping at least the closing speed towards the target
+            let time_to_go = to_target.length() / closing_speed;
+            let along_sight = velocity.linear.dot(line_of_sight);
+            let base_velocity =
+                velocity.linear + line_of_sight * (pursuit.closing_speed - along_sight).max(0.0);
+            intercept(
+                transform.position
+                    + base_velocity * time_to_go
+                    + acceleration * (0.5 * time_to_go * time_to_go),
+            )
+        }
+        PursuitLaw::Rendezvous { local_offset } => {
+            let offset = target_orientation * local_offset;
+            Aim {
+                position: target_position + offset,
+                orientation: Some(target_orientation),
+                // A spinning target swings the offset point around with it
+                velocity: target_velocity + target_spin.cross(offset),
+                angular_velocity: target_spin,
+                pass_through_speed: None,
+            }
+        }
+    }
+}
+
+// Soonest time a ship flying at a constant speed could meet a target moving at a constant
+// velocity, from |to_target + target_velocity * t| = speed * t
+fn intercept_time(to_target: Vec3, target_velocity: Vec3, speed: f32) -> Option<f32> {
+    let a = target_velocity.length_squared() - speed * speed;
+    let b = 2.0 * to_target.dot(target_velocity);
+    let c = to_target.length_squared();
+
+    if a.abs() < f32::EPSILON {
+        return (b < 0.0).then(|| -c / b);
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let root = discriminant.sqrt();
+    [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+        .into_iter()
+        .filter(|time| *time > 0.0)
+        .min_by(f32::total_cmp)
+}
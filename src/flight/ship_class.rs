@@ -0,0 +1,52 @@
+use glam::Vec3;
+
+use crate::flight::{
+    flight_components::{FlightControllerGains, ThrusterArray, ThrusterLimits},
+    pid::PidGains,
+};
+
+// Everything ships of one hull design share. Stage spawns ships from these and the gain tuner
+// runs its experiments on them.
+#[derive(Clone)]
+pub struct ShipClass {
+    pub name: &'static str,
+    pub mass: f32,
+    // Full size of the hull's box collider
+    pub hull_size: Vec3,
+    pub thrusters: ThrusterArray,
+    pub gains: FlightControllerGains,
+    // Fastest a pilot can ask the ship to fly
+    pub max_speed: f32,
+}
+
+impl ShipClass {
+    pub const NAMES: [&str; 1] = ["albatross"];
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "albatross" => Some(Self::albatross()),
+            _ => None,
+        }
+    }
+
+    pub fn albatross() -> Self {
+        let hull_size = Vec3::new(9.5484, 1.28, 4.3138);
+        Self {
+            name: "albatross",
+            mass: 5000.0,
+            hull_size,
+            // Strong main drive, with a much weaker retro engine for braking
+            thrusters: ThrusterArray::rcs_layout(hull_size / 2.0, 12500.0, 75000.0, 25000.0),
+            // From `--tune albatross`
+            gains: FlightControllerGains::new(
+                PidGains::new(14.142, 14.672, 0.336).with_output_limit(25.0),
+                PidGains::new(16.0, 16.0, 0.381).with_output_limit(12.78),
+            ),
+            max_speed: 150.0,
+        }
+    }
+
+    pub fn limits(&self) -> ThrusterLimits {
+        self.thrusters.limits()
+    }
+}
@@ -0,0 +1,131 @@
+use glam::Vec3;
+use rapier3d::na::{Matrix6, Vector6};
+
+use crate::flight::flight_components::{Thruster, ThrusterLimits};
+
+const PSEUDO_INVERSE_EPSILON: f32 = 1.0e-6;
+
+// Largest error, as a share of the ship's limit on that axis, still counted as a match
+const FIT_TOLERANCE: f32 = 1.0e-3;
+
+// Finds throttles in [0, 1] whose combined force and torque best match the requested local
+// wrench. Takes the minimum-norm solution through the pseudo-inverse of the thruster matrix,
+// then repeatedly pins any thruster that came out of range to its bound and re-solves the
+// rest for whatever is left over. Rows are scaled by the ship's limits so force and torque
+// errors count the same. The pseudo-inverse goes through the 6x6 Gram matrix so the cost
+// barely grows with the number of thrusters.
+pub fn allocate_thrust(
+    thrusters: &[Thruster],
+    local_force: Vec3,
+    local_torque: Vec3,
+    limits: &ThrusterLimits,
+) -> Vec<f32> {
+    let row_scale = limit_row(limits).map(|limit| if limit > 0.0 { 1.0 / limit } else { 0.0 });
+
+    let scale = Vector6::from_column_slice(&row_scale);
+    let columns: Vec<Vector6<f32>> = thrusters
+        .iter()
+        .map(|thruster| {
+            let column = wrench_row(thruster.max_force(), thruster.max_torque());
+            Vector6::from_column_slice(&column).component_mul(&scale)
+        })
+        .collect();
+
+    let wrench = wrench_row(local_force, local_torque);
+    let mut remaining = Vector6::from_column_slice(&wrench).component_mul(&scale);
+
+    let mut throttles = vec![0.0; thrusters.len()];
+    let mut free: Vec<usize> = (0..thrusters.len()).collect();
+
+    while !free.is_empty() {
+        let gram = free.iter().fold(Matrix6::zeros(), |gram, &index| {
+            gram + columns[index] * columns[index].transpose()
+        });
+        // Cholesky is much cheaper, the SVD is only needed once pinned thrusters leave the
+        // rest unable to push along some axis
+        let dual = match gram.cholesky() {
+            Some(cholesky) => cholesky.solve(&remaining),
+            None => match gram.pseudo_inverse(PSEUDO_INVERSE_EPSILON) {
+                Ok(gram_inverse) => gram_inverse * remaining,
+                Err(_) => break,
+            },
+        };
+        let solution: Vec<f32> = free
+            .iter()
+            .map(|&index| columns[index].dot(&dual))
+            .collect();
+
+        let saturated: Vec<usize> = (0..free.len())
+            .filter(|&col| !(0.0..=1.0).contains(&solution[col]))
+            .collect();
+
+        if saturated.is_empty() {
+            for (col, &index) in free.iter().enumerate() {
+                throttles[index] = solution[col];
+            }
+            break;
+        }
+
+        for &col in &saturated {
+            let index = free[col];
+            throttles[index] = solution[col].clamp(0.0, 1.0);
+            remaining -= columns[index] * throttles[index];
+        }
+
+        free = free
+            .iter()
+            .enumerate()
+            .filter(|(col, _index)| !saturated.contains(col))
+            .map(|(_col, &index)| index)
+            .collect();
+    }
+
+    throttles
+}
+
+// Whether the thrusters can produce a local wrench exactly, rather than just get close
+pub fn allocation_fits(
+    thrusters: &[Thruster],
+    local_force: Vec3,
+    local_torque: Vec3,
+    limits: &ThrusterLimits,
+) -> bool {
+    let throttles = allocate_thrust(thrusters, local_force, local_torque, limits);
+    let (force, torque) = wrench_at(thrusters, throttles.iter().copied());
+
+    let error = wrench_row(force - local_force, torque - local_torque);
+    let limit = limit_row(limits);
+    (0..6).all(|row| error[row].abs() <= limit[row] * FIT_TOLERANCE)
+}
+
+// Local force and torque about the centre of mass from every thruster at its current throttle
+pub fn thrust_wrench(thrusters: &[Thruster]) -> (Vec3, Vec3) {
+    wrench_at(
+        thrusters,
+        thrusters.iter().map(|thruster| thruster.throttle),
+    )
+}
+
+fn wrench_at(thrusters: &[Thruster], throttles: impl Iterator<Item = f32>) -> (Vec3, Vec3) {
+    thrusters.iter().zip(throttles).fold(
+        (Vec3::ZERO, Vec3::ZERO),
+        |(force, torque), (thruster, throttle)| {
+            (
+                force + thruster.max_force() * throttle,
+                torque + thruster.max_torque() * throttle,
+            )
+        },
+    )
+}
+
+fn wrench_row(force: Vec3, torque: Vec3) -> [f32; 6] {
+    [force.x, force.y, force.z, torque.x, torque.y, torque.z]
+}
+
+// The stronger direction of each axis sets its scale
+fn limit_row(limits: &ThrusterLimits) -> [f32; 6] {
+    wrench_row(
+        limits.max_force_positive.max(limits.max_force_negative),
+        limits.max_torque_positive.max(limits.max_torque_negative),
+    )
+}
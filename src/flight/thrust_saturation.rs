@@ -0,0 +1,175 @@
+use glam::Vec3;
+
+use crate::flight::flight_components::{SaturationMode, ThrusterLimits};
+
+// How close to the largest scale that fits a search of a thruster array gets, as a share of
+// the box's scale. Ships without one are scaled exactly.
+const SCALE_TOLERANCE: f32 = 1.0 / 256.0;
+
+pub struct SaturationScales {
+    pub saturated: bool,
+    pub linear: f32,
+    pub angular: f32,
+    pub linear_authority: f32,
+}
+
+impl SaturationScales {
+    pub const UNSATURATED: Self = Self {
+        saturated: false,
+        linear: 1.0,
+        angular: 1.0,
+        linear_authority: 1.0,
+    };
+}
+
+// Works out how far to scale a local force and torque so they fit the thrusters. Against the
+// limits box alone the scales are exact. With a thruster array, `fits` says whether the array
+// can produce a given pair and the scales are searched for below the box's, starting from
+// `previous`, last frame's scales, since commands rarely change much from one frame to the
+// next. Scaling never changes either one's direction.
+pub fn saturate(
+    mode: SaturationMode,
+    force: Vec3,
+    torque: Vec3,
+    limits: &ThrusterLimits,
+    fits: Option<&dyn Fn(Vec3, Vec3) -> bool>,
+    previous: &SaturationScales,
+) -> SaturationScales {
+    let force_bound = axis_scale(force, limits.force_limit_towards(force));
+    let torque_bound = axis_scale(torque, limits.torque_limit_towards(torque));
+
+    // Largest k for which `wrench(k)` fits, given the box allows no more than `bound`
+    let largest = |bound: f32, guess: f32, wrench: &dyn Fn(f32) -> (Vec3, Vec3)| match fits {
+        Some(fits) => largest_fitting_scale(
+            |k| {
+                let (force, torque) = wrench(k);
+                fits(force, torque)
+            },
+            bound,
+            guess,
+        ),
+        None => bound,
+    };
+
+    if force_bound.min(torque_bound) >= 1.0 && fits.is_none_or(|fits| fits(force, torque)) {
+        return SaturationScales::UNSATURATED;
+    }
+
+    let uniform = || {
+        largest(force_bound.min(torque_bound), previous.angular, &|k| {
+            (force * k, torque * k)
+        })
+    };
+
+    let (linear, angular) = match mode {
+        // Left to the clamp or the allocator
+        SaturationMode::PerAxis => (1.0, 1.0),
+        SaturationMode::Uniform => {
+            let scale = uniform();
+            (scale, scale)
+        }
+        SaturationMode::RotationPriority { rotation_priority } => {
+            let priority = rotation_priority.clamp(0.0, 1.0);
+            let rotation_alone = || {
+                largest(torque_bound, previous.angular, &|k| {
+                    (Vec3::ZERO, torque * k)
+                })
+            };
+
+            // Searches are expensive with a thruster array, so skip the one that isn't needed
+            let angular = if priority >= 1.0 {
+                rotation_alone()
+            } else if priority <= 0.0 {
+                uniform()
+            } else {
+                let uniform = uniform();
+                uniform + (rotation_alone() - uniform) * priority
+            };
+
+            // The box limits force and torque separately, so only the force bounds this one
+            let linear = largest(force_bound, previous.linear, &|k| {
+                (force * k, torque * angular)
+            });
+            (linear, angular)
+        }
+    };
+
+    let linear_authority = if mode == SaturationMode::PerAxis {
+        1.0
+    } else {
+        let guess = previous.linear / previous.linear_authority.max(f32::EPSILON);
+        let translation_alone = largest(force_bound, guess, &|k| (force * k, Vec3::ZERO));
+        if translation_alone > 0.0 {
+            (linear / translation_alone).min(1.0)
+        } else {
+            1.0
+        }
+    };
+
+    SaturationScales {
+        saturated: true,
+        linear,
+        angular,
+        linear_authority,
+    }
+}
+
+// Largest k in [0, 1] that keeps `vector * k` inside a per-axis box, where `limit` is the box's
+// extent on whichever side of each axis the vector points. Axes the vector doesn't use can't
+// limit it, an axis it does use with no limit stops it entirely.
+fn axis_scale(vector: Vec3, limit: Vec3) -> f32 {
+    (0..3)
+        .filter(|&axis| vector[axis] != 0.0)
+        .map(|axis| limit[axis] / vector[axis].abs())
+        .fold(1.0, f32::min)
+}
+
+// Largest k in [0, upper] for which `fits(k)` holds, to within SCALE_TOLERANCE, assuming it
+// holds at 0 and stops holding somewhere past it. Strides away from `guess` in growing steps
+// until the answer is bracketed, then bisects, so a guess close to the answer needs only a
+// couple of checks.
+fn largest_fitting_scale(fits: impl Fn(f32) -> bool, upper: f32, guess: f32) -> f32 {
+    if upper <= 0.0 {
+        return 0.0;
+    }
+
+    let tolerance = upper * SCALE_TOLERANCE;
+    let guess = guess.clamp(0.0, upper);
+    let mut stride = tolerance;
+
+    let (mut low, mut high) = if fits(guess) {
+        let mut low = guess;
+        loop {
+            if low >= upper {
+                return upper;
+            }
+            let next = (low + stride).min(upper);
+            if !fits(next) {
+                break (low, next);
+            }
+            low = next;
+            stride *= 2.0;
+        }
+    } else {
+        let mut high = guess;
+        loop {
+            let next = (high - stride).max(0.0);
+            if next <= 0.0 || fits(next) {
+                break (next, high);
+            }
+            high = next;
+            stride *= 2.0;
+        }
+    };
+
+    while high - low > tolerance {
+        let mid = (low + high) / 2.0;
+        if fits(mid) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    low
+}
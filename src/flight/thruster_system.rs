@@ -1,52 +1,124 @@
+use glam::Vec3;
 use hecs::World;
-use rapier3d::parry::mass_properties;
 
 use crate::{
-    flight::flight_components::{AccelerationControlCommand, ThrusterLimits},
+    flight::{
+        flight_components::{
+            AccelerationControlCommand, SaturationMode, ThrustSaturation, ThrusterArray,
+            ThrusterLimits,
+        },
+        thrust_allocation::{allocate_thrust, allocation_fits},
+        thrust_saturation::{SaturationScales, saturate},
+    },
     physics::{
         physics_components::{Forces, InertiaProperties, MassProperties},
         transform::Transform,
     },
 };
 
-pub fn thruster_system(world: &mut World) {
-    for (_entity, (transform, mass_properties, inertia_properties, forces, command, limits)) in
-        world
-            .query::<(
-                &Transform,
-                &MassProperties,
-                &InertiaProperties,
-                &mut Forces,
-                &AccelerationControlCommand,
-                &ThrusterLimits,
-            )>()
-            .iter()
+// Turns each ship's commanded acceleration into forces. Commands beyond what the ship can do
+// are scaled down according to its ThrustSaturation, per axis clamping without one. Ships with
+// a ThrusterArray get the wrench shared out between their thrusters and pushed at the nozzles,
+// the rest are clamped to the ThrusterLimits box.
+pub fn thruster_system(world: &mut World, dt: f32) {
+    for (
+        _entity,
+        (
+            transform,
+            mass_properties,
+            inertia_properties,
+            forces,
+            command,
+            limits,
+            thruster_array,
+            saturation,
+        ),
+    ) in world
+        .query::<(
+            &Transform,
+            &MassProperties,
+            &InertiaProperties,
+            &mut Forces,
+            &AccelerationControlCommand,
+            &ThrusterLimits,
+            Option<&mut ThrusterArray>,
+            Option<&mut ThrustSaturation>,
+        )>()
+        .iter()
     {
-        // ----- Linear -----
-        let desired_force = command.linear_acceleration * mass_properties.mass;
+        let mode = saturation
+            .as_ref()
+            .map_or(SaturationMode::PerAxis, |saturation| saturation.mode);
+        let previous = saturation
+            .as_ref()
+            .map_or(SaturationScales::UNSATURATED, |saturation| {
+                SaturationScales {
+                    saturated: saturation.saturated,
+                    linear: saturation.linear_scale,
+                    angular: saturation.angular_scale,
+                    linear_authority: saturation.linear_authority,
+                }
+            });
 
-        // Transform to local space to apply limits
-        let local_desired_force = transform.orientation.inverse() * desired_force;
-        let clamped_local_force = local_desired_force.clamp(-limits.max_force, limits.max_force);
+        // Desired force and torque in local space, torque through the local inertia tensor
+        let local_desired_force =
+            transform.orientation.inverse() * command.linear_acceleration * mass_properties.mass;
+        let local_angular_accel = transform.orientation.inverse() * command.angular_acceleration;
+        let local_desired_torque = inertia_properties.inertia * local_angular_accel;
 
-        // Transform back to world space
-        let world_force = transform.orientation * clamped_local_force;
-        forces.linear += world_force;
+        // The limits box is optimistic, so anything outside it can't fit and the much dearer
+        // allocation is skipped
+        let array_fits = |force, torque| {
+            thruster_array.as_ref().is_none_or(|thruster_array| {
+                limits.contains(force, torque)
+                    && allocation_fits(&thruster_array.thrusters, force, torque, limits)
+            })
+        };
+        let scales = saturate(
+            mode,
+            local_desired_force,
+            local_desired_torque,
+            limits,
+            thruster_array
+                .is_some()
+                .then_some(&array_fits as &dyn Fn(Vec3, Vec3) -> bool),
+            &previous,
+        );
 
-        // ----- Angular -----
-        // Transform commanded angular acceleration to local space FIRST
-        let local_angular_accel = transform.orientation.inverse() * command.angular_acceleration;
+        let local_force = local_desired_force * scales.linear;
+        let local_torque = local_desired_torque * scales.angular;
 
-        // Now compute torque using local-space inertia tensor
-        let local_desired_torque = inertia_properties.inertia * local_angular_accel;
+        if let Some(saturation) = saturation {
+            saturation.saturated = scales.saturated;
+            saturation.linear_scale = scales.linear;
+            saturation.angular_scale = scales.angular;
+            saturation.linear_authority = scales.linear_authority;
+        }
+
+        if let Some(thruster_array) = thruster_array {
+            let throttles =
+                allocate_thrust(&thruster_array.thrusters, local_force, local_torque, limits);
+
+            for (thruster, setpoint) in thruster_array.thrusters.iter_mut().zip(throttles) {
+                thruster.update_throttle(setpoint, dt);
+                if thruster.throttle > 0.0 {
+                    forces.add_local_force_at_local_point(
+                        thruster.max_force() * thruster.throttle,
+                        thruster.local_position,
+                    );
+                }
+            }
+
+            continue;
+        }
 
-        // Apply limits in local space
-        let clamped_local_torque =
-            local_desired_torque.clamp(-limits.max_torque, limits.max_torque);
+        // Per axis clamping, a no-op when saturation already scaled the wrench to fit
+        let clamped_local_force = limits.clamp_force(local_force);
+        let clamped_local_torque = limits.clamp_torque(local_torque);
 
         // Transform to world space for physics system
-        let world_torque = transform.orientation * clamped_local_torque;
-        forces.torque += world_torque;
+        forces.linear += transform.orientation * clamped_local_force;
+        forces.torque += transform.orientation * clamped_local_torque;
 
         // println!(
         //     "Forces - Desired: {:.2} Clamped: {:.2} | Torque - Desired: {:.2} Clamped: {:.2}",
@@ -0,0 +1,29 @@
+use hecs::World;
+
+use crate::flight::navigation_components::{NavigationQueue, NavigationStatus, NavigationTarget};
+
+// Feeds the head of each ship's NavigationQueue into its NavigationTarget, moving on to the
+// next waypoint once navigation_system has found the ship arrived at the current one. Once
+// the queue runs dry the ship holds at the last waypoint. Must run before navigation_system.
+pub fn waypoint_system(world: &mut World) {
+    for (_entity, (queue, nav_target, status)) in world.query_mut::<(
+        &mut NavigationQueue,
+        &mut NavigationTarget,
+        Option<&NavigationStatus>,
+    )>() {
+        let Some(head) = queue.waypoints.front() else {
+            continue;
+        };
+
+        // The status belongs to whatever target was flown to last frame
+        let at_head = head.target_position == nav_target.target_position
+            && head.target_orientation == nav_target.target_orientation;
+        if at_head && status.is_some_and(|status| status.arrived) {
+            queue.advance();
+        }
+
+        if let Some(head) = queue.waypoints.front() {
+            *nav_target = *head;
+        }
+    }
+}
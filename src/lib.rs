@@ -0,0 +1,12 @@
+// Everything is built through `new`, and some of it (the stage, the physics world) does real
+// work there, so a `Default` that hides that isn't wanted
+#![allow(clippy::new_without_default)]
+
+pub mod core;
+pub mod destruction;
+pub mod eva;
+pub mod flight;
+pub mod physics;
+pub mod render;
+#[cfg(test)]
+mod tests;
@@ -1,13 +1,14 @@
-mod core;
-mod flight;
-mod physics;
-mod render;
-
-use core::stage::Stage;
+use space::core::{gain_tuning::run_tuning, stage::Stage};
 
 use miniquad::{conf::Conf, *};
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--tune") {
+        let name = args.get(index + 1).map(String::as_str).unwrap_or_default();
+        std::process::exit(if run_tuning(name) { 0 } else { 1 });
+    }
+
     let conf: Conf = conf::Conf::default();
     start(conf, || {
         let mut stage = Stage::new();
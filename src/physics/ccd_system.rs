@@ -0,0 +1,33 @@
+use hecs::World;
+use rapier3d::prelude::RigidBodyHandle;
+
+use crate::physics::{
+    physics_components::ContinuousCollision, physics_config::PhysicsConfig,
+    physics_world::PhysicsWorld,
+};
+
+// Re-evaluates CCD for every body each frame, so speed thresholds follow the body's current
+// velocity and changes to the global switch in PhysicsConfig reach bodies that already exist
+pub fn ccd_system(world: &World, physics_world: &mut PhysicsWorld) {
+    for (_entity, (rb_handle, ccd)) in world
+        .query::<(&RigidBodyHandle, Option<&ContinuousCollision>)>()
+        .iter()
+    {
+        if let Some(rb) = physics_world.bodies.get_mut(*rb_handle) {
+            let enabled = ccd_enabled(&physics_world.config, ccd, rb.linvel().norm());
+            if rb.is_ccd_enabled() != enabled {
+                rb.enable_ccd(enabled);
+            }
+
+            let soft_ccd_prediction = ccd.map_or(0.0, |ccd| ccd.soft_ccd_prediction);
+            if rb.soft_ccd_prediction() != soft_ccd_prediction {
+                rb.set_soft_ccd_prediction(soft_ccd_prediction);
+            }
+        }
+    }
+}
+
+// The global switch gates everything, per-entity settings can only turn CCD off below it
+pub fn ccd_enabled(config: &PhysicsConfig, ccd: Option<&ContinuousCollision>, speed: f32) -> bool {
+    config.ccd_enabled && ccd.is_none_or(|ccd| ccd.is_enabled(speed))
+}
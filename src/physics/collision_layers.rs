@@ -0,0 +1,127 @@
+use std::fs::read_to_string;
+
+use rapier3d::prelude::{Group, InteractionGroups};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CollisionLayer {
+    Ships,
+    Projectiles,
+    Debris,
+    Sensors,
+    Terrain,
+    Characters,
+}
+
+impl CollisionLayer {
+    pub const ALL: [CollisionLayer; 6] = [
+        CollisionLayer::Ships,
+        CollisionLayer::Projectiles,
+        CollisionLayer::Debris,
+        CollisionLayer::Sensors,
+        CollisionLayer::Terrain,
+        CollisionLayer::Characters,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ships" => Some(CollisionLayer::Ships),
+            "projectiles" => Some(CollisionLayer::Projectiles),
+            "debris" => Some(CollisionLayer::Debris),
+            "sensors" => Some(CollisionLayer::Sensors),
+            "terrain" => Some(CollisionLayer::Terrain),
+            "characters" => Some(CollisionLayer::Characters),
+            _ => None,
+        }
+    }
+
+    pub fn group(self) -> Group {
+        Group::from_bits_truncate(1 << self.index())
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// Which layers a layer generates contacts with, and which of those contacts push
+#[derive(Copy, Clone)]
+pub struct LayerRules {
+    pub collides_with: Group,
+    pub solver_with: Group,
+}
+
+pub struct CollisionLayerTable {
+    rules: [LayerRules; CollisionLayer::ALL.len()],
+}
+
+impl CollisionLayerTable {
+    // Every layer collides with and pushes every other layer
+    pub fn all() -> Self {
+        Self {
+            rules: [LayerRules {
+                collides_with: Group::ALL,
+                solver_with: Group::ALL,
+            }; CollisionLayer::ALL.len()],
+        }
+    }
+
+    pub fn load(path: &str) -> Self {
+        let text = read_to_string(path)
+            .unwrap_or_else(|_| panic!("Failed to read collision layers: {}", path));
+
+        let mut table = Self::all();
+        let mut current: Option<CollisionLayer> = None;
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = Some(
+                    CollisionLayer::from_name(name.trim())
+                        .unwrap_or_else(|| panic!("Unknown collision layer: {}", name)),
+                );
+                continue;
+            }
+
+            let (Some(layer), Some((key, value))) = (current, line.split_once('=')) else {
+                panic!("Malformed collision layer line: {}", line);
+            };
+
+            let groups = parse_groups(value);
+            let rules = &mut table.rules[layer.index()];
+            match key.trim() {
+                "collides" => rules.collides_with = groups,
+                "solver" => rules.solver_with = groups,
+                other => panic!("Unknown collision layer key: {}", other),
+            }
+        }
+
+        table
+    }
+
+    pub fn rules(&self, layer: CollisionLayer) -> LayerRules {
+        self.rules[layer.index()]
+    }
+
+    pub fn interaction_groups(&self, layer: CollisionLayer) -> InteractionGroups {
+        InteractionGroups::new(layer.group(), self.rules(layer).collides_with)
+    }
+
+    pub fn solver_groups(&self, layer: CollisionLayer) -> InteractionGroups {
+        InteractionGroups::new(layer.group(), self.rules(layer).solver_with)
+    }
+}
+
+fn parse_groups(value: &str) -> Group {
+    value
+        .split_whitespace()
+        .map(|name| {
+            CollisionLayer::from_name(name)
+                .unwrap_or_else(|| panic!("Unknown collision layer: {}", name))
+                .group()
+        })
+        .fold(Group::NONE, |groups, group| groups | group)
+}
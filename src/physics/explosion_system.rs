@@ -0,0 +1,141 @@
+use glam::Vec3;
+use hecs::{Entity, World};
+use rapier3d::prelude::*;
+
+use crate::{
+    destruction::destruction_components::HullIntegrity,
+    physics::{
+        physics_components::{Explosion, Forces},
+        physics_hooks::entity_from_user_data,
+        physics_world::PhysicsWorld,
+    },
+};
+
+// Applies every pending Explosion as impulses at the nearest surface point of each body in
+// range, so off-centre hits spin bodies as well as pushing them. Bodies with no clear line of
+// sight to the centre are shielded. Must run before sync_ecs_to_rapier.
+pub fn explosion_system(world: &mut World, physics_world: &mut PhysicsWorld) {
+    let explosions: Vec<(Entity, Vec3, f32, f32, f32, _)> = world
+        .query::<&Explosion>()
+        .iter()
+        .map(|(entity, explosion)| {
+            (
+                entity,
+                explosion.center,
+                explosion.radius,
+                explosion.peak_impulse,
+                explosion.peak_damage,
+                explosion.falloff,
+            )
+        })
+        .collect();
+
+    if explosions.is_empty() {
+        return;
+    }
+
+    // Bodies spawned since the last step aren't in the query pipeline yet
+    physics_world
+        .query_pipeline
+        .update(&physics_world.colliders);
+
+    let mut impulses = Vec::new();
+
+    for (explosion_entity, center, radius, peak_impulse, peak_damage, falloff) in explosions {
+        let center_point = point![center.x, center.y, center.z];
+        let blast = Ball::new(radius);
+        let blast_position = Isometry::translation(center.x, center.y, center.z);
+
+        let mut affected = Vec::new();
+        physics_world.query_pipeline.intersections_with_shape(
+            &physics_world.bodies,
+            &physics_world.colliders,
+            &blast_position,
+            &blast,
+            QueryFilter::only_dynamic(),
+            |collider_handle| {
+                affected.push(collider_handle);
+                true
+            },
+        );
+
+        for collider_handle in affected {
+            let collider = &physics_world.colliders[collider_handle];
+            let Some(entity) = entity_from_user_data(collider.user_data) else {
+                continue;
+            };
+
+            let projection =
+                collider
+                    .shape()
+                    .project_point(collider.position(), &center_point, true);
+
+            let surface_point = projection.point;
+            let to_surface = surface_point - center_point;
+            let distance = to_surface.norm();
+
+            // Centre inside the collider, push out from the collider's centre instead
+            let direction = if projection.is_inside || distance < 1e-4 {
+                let outward = collider.translation() - center_point.coords;
+                if outward.norm() < 1e-4 {
+                    continue;
+                }
+                outward.normalize()
+            } else {
+                if is_occluded(physics_world, center_point, to_surface, collider_handle) {
+                    continue;
+                }
+                to_surface / distance
+            };
+
+            let strength = falloff.factor(distance / radius);
+            if strength <= 0.0 {
+                continue;
+            }
+
+            let impulse = direction * peak_impulse * strength;
+            impulses.push((
+                entity,
+                Vec3::new(impulse.x, impulse.y, impulse.z),
+                Vec3::new(surface_point.x, surface_point.y, surface_point.z),
+                peak_damage * strength,
+            ));
+        }
+
+        world
+            .despawn(explosion_entity)
+            .expect("Explosion entity should exist");
+    }
+
+    for (entity, impulse, point, damage) in impulses {
+        if let Ok(mut forces) = world.get::<&mut Forces>(entity) {
+            forces.apply_impulse_at_point(impulse, point);
+        }
+        if let Ok(mut integrity) = world.get::<&mut HullIntegrity>(entity) {
+            integrity.apply_damage(damage);
+        }
+    }
+}
+
+// Something else sits between the centre and the surface point
+fn is_occluded(
+    physics_world: &PhysicsWorld,
+    center: Point<Real>,
+    to_surface: Vector<Real>,
+    target: ColliderHandle,
+) -> bool {
+    let distance = to_surface.norm();
+    let ray = Ray::new(center, to_surface / distance);
+
+    // Stop just short of the surface so the target itself isn't reported
+    let hit = physics_world.query_pipeline.cast_ray(
+        &physics_world.bodies,
+        &physics_world.colliders,
+        &ray,
+        distance - 1e-3,
+        true,
+        QueryFilter::new().exclude_collider(target),
+    );
+
+    hit.is_some()
+}
@@ -0,0 +1,68 @@
+use glam::{Mat3, Vec3};
+use hecs::World;
+use rapier3d::prelude::*;
+
+use crate::physics::{
+    physics_components::{Damping, InertiaProperties, MassProperties, PhysicsMaterial},
+    physics_world::PhysicsWorld,
+};
+
+// Pushes runtime changes to PhysicsMaterial and Damping into rapier. Creation-time values are
+// applied by sync_new_entities.
+pub fn material_system(world: &mut World, physics_world: &mut PhysicsWorld) {
+    for (_entity, (material, collider_handle, mass_properties, inertia_properties)) in world
+        .query_mut::<(
+            &PhysicsMaterial,
+            &ColliderHandle,
+            Option<&mut MassProperties>,
+            Option<&mut InertiaProperties>,
+        )>()
+    {
+        let Some(collider) = physics_world.colliders.get_mut(*collider_handle) else {
+            continue;
+        };
+
+        if collider.friction() != material.friction {
+            collider.set_friction(material.friction);
+        }
+        if collider.restitution() != material.restitution {
+            collider.set_restitution(material.restitution);
+        }
+        if collider.friction_combine_rule() != material.friction_combine_rule {
+            collider.set_friction_combine_rule(material.friction_combine_rule);
+        }
+        if collider.restitution_combine_rule() != material.restitution_combine_rule {
+            collider.set_restitution_combine_rule(material.restitution_combine_rule);
+        }
+
+        if let Some(density) = material.density
+            && collider.density() != density
+        {
+            collider.set_density(density);
+
+            // Keep the ECS copies in step so the flight systems plan with the new mass
+            if let Some(mass_properties) = mass_properties {
+                *mass_properties = MassProperties::new(collider.mass());
+            }
+            if let Some(inertia_properties) = inertia_properties {
+                let inertia = collider.mass_properties().principal_inertia();
+                *inertia_properties = InertiaProperties::new(Mat3::from_diagonal(Vec3::new(
+                    inertia.x, inertia.y, inertia.z,
+                )));
+            }
+        }
+    }
+
+    for (_entity, (damping, rb_handle)) in world.query_mut::<(&Damping, &RigidBodyHandle)>() {
+        let Some(rb) = physics_world.bodies.get_mut(*rb_handle) else {
+            continue;
+        };
+
+        if rb.linear_damping() != damping.linear {
+            rb.set_linear_damping(damping.linear);
+        }
+        if rb.angular_damping() != damping.angular {
+            rb.set_angular_damping(damping.angular);
+        }
+    }
+}
@@ -1,5 +1,13 @@
+pub mod ccd_system;
+pub mod collision_layers;
+pub mod explosion_system;
+pub mod material_system;
 pub mod physics_components;
+pub mod physics_config;
+pub mod physics_hooks;
 pub mod physics_system;
 pub mod physics_world;
+pub mod rotating_frame_system;
+pub mod spatial_hash;
 pub mod sync_physics;
 pub mod transform;
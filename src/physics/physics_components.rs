@@ -1,16 +1,16 @@
 use glam::{Mat3, Vec3};
+use hecs::Entity;
+use rapier3d::prelude::CoefficientCombineRule;
+
+use crate::physics::collision_layers::CollisionLayer;
 
 pub struct MassProperties {
     pub mass: f32,
-    pub inverse_mass: f32,
 }
 
 impl MassProperties {
     pub fn new(mass: f32) -> Self {
-        Self {
-            mass: mass,
-            inverse_mass: 1.0 / mass,
-        }
+        Self { mass }
     }
 }
 
@@ -22,7 +22,7 @@ pub struct InertiaProperties {
 impl InertiaProperties {
     pub fn new(inertia: Mat3) -> Self {
         Self {
-            inertia: inertia,
+            inertia,
             inverse_inertia: inertia.inverse(),
         }
     }
@@ -40,20 +40,78 @@ impl Velocity {
     };
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ForceFrame {
+    World,
+    // Both the vector and the point are relative to the body and rotate with it, and the point
+    // is an offset from the centre of mass rather than the body's origin
+    Local,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PointForce {
+    pub vector: Vec3,
+    pub point: Vec3,
+    pub frame: ForceFrame,
+}
+
+// Accumulated for one frame, cleared by sync_rapier_to_ecs
 pub struct Forces {
+    // Applied at the centre of mass
     pub linear: Vec3,
     pub torque: Vec3,
+
+    // Forces and impulses away from the centre of mass also induce torque
+    pub point_forces: Vec<PointForce>,
+    pub point_impulses: Vec<PointForce>,
 }
 
 impl Forces {
-    pub fn new(linear: Vec3, torque: Vec3) -> Self {
-        Self { linear, torque }
-    }
-
     pub const ZERO: Self = Self {
         linear: Vec3::ZERO,
         torque: Vec3::ZERO,
+        point_forces: Vec::new(),
+        point_impulses: Vec::new(),
     };
+
+    pub fn add_force_at_point(&mut self, force: Vec3, point: Vec3) {
+        self.point_forces.push(PointForce {
+            vector: force,
+            point,
+            frame: ForceFrame::World,
+        });
+    }
+
+    pub fn add_local_force_at_local_point(&mut self, force: Vec3, point: Vec3) {
+        self.point_forces.push(PointForce {
+            vector: force,
+            point,
+            frame: ForceFrame::Local,
+        });
+    }
+
+    pub fn apply_impulse_at_point(&mut self, impulse: Vec3, point: Vec3) {
+        self.point_impulses.push(PointForce {
+            vector: impulse,
+            point,
+            frame: ForceFrame::World,
+        });
+    }
+
+    pub fn apply_local_impulse_at_local_point(&mut self, impulse: Vec3, point: Vec3) {
+        self.point_impulses.push(PointForce {
+            vector: impulse,
+            point,
+            frame: ForceFrame::Local,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.linear = Vec3::ZERO;
+        self.torque = Vec3::ZERO;
+        self.point_forces.clear();
+        self.point_impulses.clear();
+    }
 }
 
 pub struct BoxCollider {
@@ -67,3 +125,235 @@ impl BoxCollider {
         }
     }
 }
+
+pub struct PhysicsMaterial {
+    pub friction: f32,
+    pub restitution: f32,
+    pub friction_combine_rule: CoefficientCombineRule,
+    pub restitution_combine_rule: CoefficientCombineRule,
+    // When set, the collider's mass comes from density * volume and MassProperties is
+    // overwritten to match. Otherwise MassProperties.mass is used as-is.
+    pub density: Option<f32>,
+}
+
+impl PhysicsMaterial {
+    pub fn new(friction: f32, restitution: f32) -> Self {
+        Self {
+            friction,
+            restitution,
+            friction_combine_rule: CoefficientCombineRule::Average,
+            restitution_combine_rule: CoefficientCombineRule::Average,
+            density: None,
+        }
+    }
+
+    // Hull plating: grinds along on contact, barely bounces
+    pub fn metal() -> Self {
+        Self {
+            friction_combine_rule: CoefficientCombineRule::Max,
+            restitution_combine_rule: CoefficientCombineRule::Min,
+            ..Self::new(0.6, 0.05)
+        }
+    }
+
+    // Loose rubble: bounces off whatever it hits
+    pub fn rubbery_rock() -> Self {
+        Self {
+            restitution_combine_rule: CoefficientCombineRule::Max,
+            ..Self::new(0.4, 0.8)
+        }
+    }
+}
+
+pub struct Damping {
+    pub linear: f32,
+    pub angular: f32,
+}
+
+impl Damping {
+    pub fn new(linear: f32, angular: f32) -> Self {
+        Self { linear, angular }
+    }
+}
+
+// Marks a body that never moves, e.g. station hulls and walls
+pub struct FixedBody;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CcdMode {
+    Always,
+    Never,
+    // Enabled only while the body's linear speed exceeds the threshold (m/s)
+    AboveSpeed(f32),
+}
+
+pub struct ContinuousCollision {
+    pub mode: CcdMode,
+    // Distance ahead of the body that contacts are predicted for. Cheaper than full CCD and
+    // enough for thin colliders that only need to see a contact one step early. 0 disables it.
+    pub soft_ccd_prediction: f32,
+}
+
+impl ContinuousCollision {
+    pub fn new(mode: CcdMode) -> Self {
+        Self {
+            mode,
+            soft_ccd_prediction: 0.0,
+        }
+    }
+
+    pub fn with_soft_ccd(mode: CcdMode, soft_ccd_prediction: f32) -> Self {
+        Self {
+            mode,
+            soft_ccd_prediction,
+        }
+    }
+
+    pub fn is_enabled(&self, speed: f32) -> bool {
+        match self.mode {
+            CcdMode::Always => true,
+            CcdMode::Never => false,
+            CcdMode::AboveSpeed(threshold) => speed > threshold,
+        }
+    }
+}
+
+pub struct CollisionFilter {
+    pub layer: CollisionLayer,
+    // Entity this collider never interacts with, e.g. the ship that fired a projectile
+    pub owner: Option<Entity>,
+}
+
+impl CollisionFilter {
+    pub fn new(layer: CollisionLayer) -> Self {
+        Self { layer, owner: None }
+    }
+
+    pub fn with_owner(layer: CollisionLayer, owner: Entity) -> Self {
+        Self {
+            layer,
+            owner: Some(owner),
+        }
+    }
+}
+
+// Collisions between the two bodies are ignored while docked. docking_system adds it when a
+// ship docks at a port on `with`.
+pub struct Docked {
+    pub with: Entity,
+}
+
+// Only contacts whose normal, pointing out of this collider, lies within `allowed_angle`
+// of `local_normal` are kept. Anything approaching from the other side passes through.
+pub struct OneWayShield {
+    pub local_normal: Vec3,
+    pub allowed_angle: f32,
+}
+
+impl OneWayShield {
+    pub fn new(local_normal: Vec3, allowed_angle: f32) -> Self {
+        Self {
+            local_normal: local_normal.normalize(),
+            allowed_angle,
+        }
+    }
+}
+
+// Velocity the collider's surface drags touching bodies along with, in the body's local
+// frame. Used for tractor surfaces and conveyors.
+pub struct SurfaceVelocity {
+    pub local_velocity: Vec3,
+}
+
+impl SurfaceVelocity {
+    pub fn new(local_velocity: Vec3) -> Self {
+        Self { local_velocity }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Falloff {
+    Linear,
+    // Smooth ease-out, full strength near the centre and no hard edge at the radius
+    Quadratic,
+}
+
+impl Falloff {
+    // Scale applied to the peak impulse at `t` = distance / radius, in [0, 1]
+    pub fn factor(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Falloff::Linear => 1.0 - t,
+            Falloff::Quadratic => (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+// One-shot area impulse. Spawn it as its own entity, explosion_system applies it on the next
+// frame and despawns it.
+pub struct Explosion {
+    pub center: Vec3,
+    pub radius: f32,
+    pub peak_impulse: f32,
+    // Hull damage at the centre, scaled by the same falloff as the impulse
+    pub peak_damage: f32,
+    pub falloff: Falloff,
+}
+
+impl Explosion {
+    pub fn new(
+        center: Vec3,
+        radius: f32,
+        peak_impulse: f32,
+        peak_damage: f32,
+        falloff: Falloff,
+    ) -> Self {
+        Self {
+            center,
+            radius,
+            peak_impulse,
+            peak_damage,
+            falloff,
+        }
+    }
+}
+
+// Interior volume of a spinning station, an annulus around the station's local `axis`.
+// Bodies inside are simulated in the station's co-rotating frame: the station itself should
+// not rotate in the physics world, and rotating_frame_system adds the centrifugal, Coriolis
+// and Euler pseudo-forces that the spin would produce.
+pub struct RotatingFrame {
+    pub axis: Vec3,
+    // Spin rate about `axis` in rad/s
+    pub spin_rate: f32,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub half_length: f32,
+
+    // Spin rate last frame, for the Euler force while spinning up or down
+    pub prev_spin_rate: f32,
+}
+
+impl RotatingFrame {
+    pub fn new(
+        axis: Vec3,
+        spin_rate: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+        half_length: f32,
+    ) -> Self {
+        Self {
+            axis: axis.normalize(),
+            spin_rate,
+            inner_radius,
+            outer_radius,
+            half_length,
+            prev_spin_rate: spin_rate,
+        }
+    }
+
+    // Spin needed for `gravity` m/s^2 at `radius`
+    pub fn spin_rate_for_gravity(gravity: f32, radius: f32) -> f32 {
+        (gravity / radius).sqrt()
+    }
+}
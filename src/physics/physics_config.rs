@@ -0,0 +1,180 @@
+use std::{fs::read_to_string, num::NonZeroUsize};
+
+use rapier3d::prelude::{IntegrationParameters, RigidBodyActivation};
+
+#[derive(Clone, Debug)]
+pub struct PhysicsConfig {
+    // Solver
+    pub solver_iterations: usize,
+    pub additional_friction_iterations: usize,
+    pub internal_pgs_iterations: usize,
+    pub internal_stabilization_iterations: usize,
+    // Number of pipeline steps taken per frame, each with dt / substeps
+    pub substeps: usize,
+    pub contact_natural_frequency: f32,
+    pub contact_damping_ratio: f32,
+    pub prediction_distance: f32,
+
+    // Master switch, ContinuousCollision settings only apply while it is on
+    pub ccd_enabled: bool,
+    pub max_ccd_substeps: usize,
+
+    // Sleeping
+    pub can_sleep: bool,
+    pub sleep_linear_threshold: f32,
+    pub sleep_angular_threshold: f32,
+    pub time_until_sleep: f32,
+
+    pub contact_skin: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        let params = IntegrationParameters::default();
+        let activation = RigidBodyActivation::active();
+
+        Self {
+            solver_iterations: params.num_solver_iterations.get(),
+            additional_friction_iterations: params.num_additional_friction_iterations,
+            internal_pgs_iterations: params.num_internal_pgs_iterations,
+            internal_stabilization_iterations: params.num_internal_stabilization_iterations,
+            substeps: 1,
+            contact_natural_frequency: params.contact_natural_frequency,
+            contact_damping_ratio: params.contact_damping_ratio,
+            prediction_distance: params.normalized_prediction_distance,
+            ccd_enabled: false,
+            max_ccd_substeps: params.max_ccd_substeps,
+            can_sleep: true,
+            sleep_linear_threshold: activation.normalized_linear_threshold,
+            sleep_angular_threshold: activation.angular_threshold,
+            time_until_sleep: activation.time_until_sleep,
+            contact_skin: 0.0,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    pub const KEYS: [&'static str; 15] = [
+        "solver_iterations",
+        "additional_friction_iterations",
+        "internal_pgs_iterations",
+        "internal_stabilization_iterations",
+        "substeps",
+        "contact_natural_frequency",
+        "contact_damping_ratio",
+        "prediction_distance",
+        "ccd_enabled",
+        "max_ccd_substeps",
+        "can_sleep",
+        "sleep_linear_threshold",
+        "sleep_angular_threshold",
+        "time_until_sleep",
+        "contact_skin",
+    ];
+
+    pub fn load(path: &str) -> Self {
+        let text = read_to_string(path)
+            .unwrap_or_else(|_| panic!("Failed to read physics config: {}", path));
+
+        let mut config = Self::default();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                panic!("Malformed physics config line: {}", line);
+            };
+
+            if let Err(err) = config.set(key.trim(), value.trim()) {
+                panic!("Invalid physics config line '{}': {}", line, err);
+            }
+        }
+
+        config
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "solver_iterations" => self.solver_iterations.to_string(),
+            "additional_friction_iterations" => self.additional_friction_iterations.to_string(),
+            "internal_pgs_iterations" => self.internal_pgs_iterations.to_string(),
+            "internal_stabilization_iterations" => {
+                self.internal_stabilization_iterations.to_string()
+            }
+            "substeps" => self.substeps.to_string(),
+            "contact_natural_frequency" => format!("{:?}", self.contact_natural_frequency),
+            "contact_damping_ratio" => format!("{:?}", self.contact_damping_ratio),
+            "prediction_distance" => format!("{:?}", self.prediction_distance),
+            "ccd_enabled" => self.ccd_enabled.to_string(),
+            "max_ccd_substeps" => self.max_ccd_substeps.to_string(),
+            "can_sleep" => self.can_sleep.to_string(),
+            "sleep_linear_threshold" => format!("{:?}", self.sleep_linear_threshold),
+            "sleep_angular_threshold" => format!("{:?}", self.sleep_angular_threshold),
+            "time_until_sleep" => format!("{:?}", self.time_until_sleep),
+            "contact_skin" => format!("{:?}", self.contact_skin),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("could not parse '{}'", value))
+        }
+
+        match key {
+            "solver_iterations" => self.solver_iterations = parse::<usize>(value)?.max(1),
+            "additional_friction_iterations" => self.additional_friction_iterations = parse(value)?,
+            "internal_pgs_iterations" => self.internal_pgs_iterations = parse(value)?,
+            "internal_stabilization_iterations" => {
+                self.internal_stabilization_iterations = parse(value)?
+            }
+            "substeps" => self.substeps = parse::<usize>(value)?.max(1),
+            "contact_natural_frequency" => self.contact_natural_frequency = parse(value)?,
+            "contact_damping_ratio" => self.contact_damping_ratio = parse(value)?,
+            "prediction_distance" => self.prediction_distance = parse(value)?,
+            "ccd_enabled" => self.ccd_enabled = parse(value)?,
+            "max_ccd_substeps" => self.max_ccd_substeps = parse(value)?,
+            "can_sleep" => self.can_sleep = parse(value)?,
+            "sleep_linear_threshold" => self.sleep_linear_threshold = parse(value)?,
+            "sleep_angular_threshold" => self.sleep_angular_threshold = parse(value)?,
+            "time_until_sleep" => self.time_until_sleep = parse(value)?,
+            "contact_skin" => self.contact_skin = parse(value)?,
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+
+        Ok(())
+    }
+
+    pub fn apply_to_integration_parameters(&self, params: &mut IntegrationParameters) {
+        params.num_solver_iterations =
+            NonZeroUsize::new(self.solver_iterations).unwrap_or(NonZeroUsize::MIN);
+        params.num_additional_friction_iterations = self.additional_friction_iterations;
+        params.num_internal_pgs_iterations = self.internal_pgs_iterations;
+        params.num_internal_stabilization_iterations = self.internal_stabilization_iterations;
+        params.contact_natural_frequency = self.contact_natural_frequency;
+        params.contact_damping_ratio = self.contact_damping_ratio;
+        params.normalized_prediction_distance = self.prediction_distance;
+        params.max_ccd_substeps = self.max_ccd_substeps;
+    }
+
+    pub fn apply_to_activation(&self, activation: &mut RigidBodyActivation) {
+        activation.normalized_linear_threshold = if self.can_sleep {
+            self.sleep_linear_threshold
+        } else {
+            -1.0
+        };
+        activation.angular_threshold = if self.can_sleep {
+            self.sleep_angular_threshold
+        } else {
+            -1.0
+        };
+        activation.time_until_sleep = self.time_until_sleep;
+    }
+}
@@ -0,0 +1,205 @@
+use hecs::{Entity, World};
+use rapier3d::prelude::*;
+
+use crate::physics::{
+    physics_components::{Docked, OneWayShield, SurfaceVelocity},
+    physics_world::PhysicsWorld,
+};
+
+// Collider user data layout: low 64 bits hold the owning entity, high 64 bits hold the
+// entity that spawned it (e.g. the ship that fired a projectile), or 0 if none
+pub fn encode_user_data(entity: Entity, owner: Option<Entity>) -> u128 {
+    let owner_bits = owner.map_or(0, |owner| owner.to_bits().get());
+    ((owner_bits as u128) << 64) | entity.to_bits().get() as u128
+}
+
+pub fn entity_from_user_data(user_data: u128) -> Option<Entity> {
+    Entity::from_bits(user_data as u64)
+}
+
+pub fn owner_from_user_data(user_data: u128) -> Option<Entity> {
+    Entity::from_bits((user_data >> 64) as u64)
+}
+
+// Turns rapier hook callbacks on for the colliders whose entities need them. Components
+// like Docked come and go at runtime, so this runs every frame before the physics step.
+pub fn sync_active_hooks(world: &World, physics_world: &mut PhysicsWorld) {
+    for (entity, collider_handle) in world.query::<&ColliderHandle>().iter() {
+        let Some(collider) = physics_world.colliders.get_mut(*collider_handle) else {
+            continue;
+        };
+
+        let mut hooks = ActiveHooks::empty();
+
+        if owner_from_user_data(collider.user_data).is_some()
+            || world.satisfies::<&Docked>(entity).unwrap_or(false)
+        {
+            hooks |= ActiveHooks::FILTER_CONTACT_PAIRS | ActiveHooks::FILTER_INTERSECTION_PAIR;
+        }
+
+        if world
+            .satisfies::<hecs::Or<&OneWayShield, &SurfaceVelocity>>(entity)
+            .unwrap_or(false)
+        {
+            hooks |= ActiveHooks::MODIFY_SOLVER_CONTACTS;
+        }
+
+        if collider.active_hooks() != hooks {
+            collider.set_active_hooks(hooks);
+        }
+    }
+}
+
+pub struct EcsPhysicsHooks<'a> {
+    pub world: &'a World,
+}
+
+impl EcsPhysicsHooks<'_> {
+    fn should_ignore_pair(
+        &self,
+        colliders: &ColliderSet,
+        c1: ColliderHandle,
+        c2: ColliderHandle,
+    ) -> bool {
+        let data1 = colliders[c1].user_data;
+        let data2 = colliders[c2].user_data;
+
+        // Projectiles never hit whatever fired them
+        let spawned_by = |spawned: u128, other: u128| {
+            owner_from_user_data(spawned).is_some()
+                && owner_from_user_data(spawned) == entity_from_user_data(other)
+        };
+        if spawned_by(data1, data2) || spawned_by(data2, data1) {
+            return true;
+        }
+
+        let (Some(entity1), Some(entity2)) =
+            (entity_from_user_data(data1), entity_from_user_data(data2))
+        else {
+            return false;
+        };
+
+        let docked_with = |entity: Entity, other: Entity| {
+            self.world
+                .get::<&Docked>(entity)
+                .is_ok_and(|docked| docked.with == other)
+        };
+
+        docked_with(entity1, entity2) || docked_with(entity2, entity1)
+    }
+}
+
+impl PhysicsHooks for EcsPhysicsHooks<'_> {
+    fn filter_contact_pair(&self, context: &PairFilterContext) -> Option<SolverFlags> {
+        if self.should_ignore_pair(context.colliders, context.collider1, context.collider2) {
+            return None;
+        }
+
+        Some(SolverFlags::COMPUTE_IMPULSES)
+    }
+
+    fn filter_intersection_pair(&self, context: &PairFilterContext) -> bool {
+        !self.should_ignore_pair(context.colliders, context.collider1, context.collider2)
+    }
+
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        let collider1 = &context.colliders[context.collider1];
+        let collider2 = &context.colliders[context.collider2];
+        let entity1 = entity_from_user_data(collider1.user_data);
+        let entity2 = entity_from_user_data(collider2.user_data);
+
+        // Both sides can be shielded, and the pair only has one state to share between them
+        let mut shields = [None, None];
+
+        for (side, (entity, is_first)) in
+            [(entity1, true), (entity2, false)].into_iter().enumerate()
+        {
+            let Some(entity) = entity else {
+                continue;
+            };
+
+            if let Ok(shield) = self.world.get::<&OneWayShield>(entity) {
+                shields[side] = Some(ShieldSide {
+                    allowed_local_normal: vector![
+                        shield.local_normal.x,
+                        shield.local_normal.y,
+                        shield.local_normal.z
+                    ],
+                    allowed_angle: shield.allowed_angle,
+                    is_first,
+                });
+            }
+
+            if let Ok(surface) = self.world.get::<&SurfaceVelocity>(entity) {
+                let collider = if is_first { collider1 } else { collider2 };
+                let velocity = collider.rotation()
+                    * vector![
+                        surface.local_velocity.x,
+                        surface.local_velocity.y,
+                        surface.local_velocity.z
+                    ];
+
+                // Tangent velocity is expressed as collider1's surface moving relative to collider2
+                let velocity = if is_first { velocity } else { -velocity };
+                for solver_contact in context.solver_contacts.iter_mut() {
+                    solver_contact.tangent_velocity += velocity;
+                }
+            }
+        }
+
+        if shields.iter().any(Option::is_some) {
+            update_as_one_way(context, &shields);
+        }
+    }
+}
+
+struct ShieldSide {
+    allowed_local_normal: Vector<Real>,
+    allowed_angle: Real,
+    is_first: bool,
+}
+
+// rapier's update_as_oneway_platform only works when the platform is collider1, this
+// follows the same state machine for either side of the pair. With a shield on each side the
+// contact is only allowed if both of them allow it.
+fn update_as_one_way(context: &mut ContactModificationContext, shields: &[Option<ShieldSide>; 2]) {
+    const CONTACT_CONFIGURATION_UNKNOWN: u32 = 0;
+    const CONTACT_CURRENTLY_ALLOWED: u32 = 1;
+    const CONTACT_CURRENTLY_FORBIDDEN: u32 = 2;
+
+    let contact_is_ok = shields.iter().flatten().all(|shield| {
+        let local_normal = if shield.is_first {
+            context.manifold.local_n1
+        } else {
+            context.manifold.local_n2
+        };
+        local_normal.dot(&shield.allowed_local_normal) >= shield.allowed_angle.cos()
+    });
+
+    match *context.user_data {
+        CONTACT_CONFIGURATION_UNKNOWN => {
+            if contact_is_ok {
+                *context.user_data = CONTACT_CURRENTLY_ALLOWED;
+            } else {
+                context.solver_contacts.clear();
+                // A zero normal means the shapes only just touch, wait a frame to decide
+                if context.manifold.local_n1.norm_squared() > 0.1 {
+                    *context.user_data = CONTACT_CURRENTLY_FORBIDDEN;
+                }
+            }
+        }
+        CONTACT_CURRENTLY_FORBIDDEN => {
+            // Keep letting the body through until it's fully out the other side
+            if contact_is_ok && context.solver_contacts.iter().all(|c| c.dist > 0.0) {
+                *context.user_data = CONTACT_CURRENTLY_ALLOWED;
+            } else {
+                context.solver_contacts.clear();
+            }
+        }
+        _ => {
+            if context.solver_contacts.is_empty() {
+                *context.user_data = CONTACT_CONFIGURATION_UNKNOWN;
+            }
+        }
+    }
+}
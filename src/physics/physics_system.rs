@@ -1,27 +1,33 @@
+use hecs::World;
 use rapier3d::prelude::*;
 
-use crate::physics::physics_world::PhysicsWorld;
+use crate::physics::{physics_hooks::EcsPhysicsHooks, physics_world::PhysicsWorld};
 
-pub fn physics_system(physics_world: &mut PhysicsWorld, dt: f32) {
+pub fn physics_system(physics_world: &mut PhysicsWorld, world: &World, dt: f32) {
     let gravity = vector![0.0, 0.0, 0.0];
-    // let physics_hooks = ();
     // let event_handler = ();
 
-    physics_world.integration_parameters.dt = dt;
+    // Forces added by sync_ecs_to_rapier persist across substeps until sync_rapier_to_ecs
+    let physics_hooks = EcsPhysicsHooks { world };
 
-    physics_world.physics_pipeline.step(
-        &gravity,
-        &physics_world.integration_parameters,
-        &mut physics_world.islands,
-        &mut physics_world.broad_phase,
-        &mut physics_world.narrow_phase,
-        &mut physics_world.bodies,
-        &mut physics_world.colliders,
-        &mut physics_world.impulse_joints,
-        &mut physics_world.multibody_joints,
-        &mut physics_world.ccd_solver,
-        None,
-        &(),
-        &(),
-    );
+    let substeps = physics_world.config.substeps.max(1);
+    physics_world.integration_parameters.dt = dt / substeps as f32;
+
+    for _ in 0..substeps {
+        physics_world.physics_pipeline.step(
+            &gravity,
+            &physics_world.integration_parameters,
+            &mut physics_world.islands,
+            &mut physics_world.broad_phase,
+            &mut physics_world.narrow_phase,
+            &mut physics_world.bodies,
+            &mut physics_world.colliders,
+            &mut physics_world.impulse_joints,
+            &mut physics_world.multibody_joints,
+            &mut physics_world.ccd_solver,
+            Some(&mut physics_world.query_pipeline),
+            &physics_hooks,
+            &(),
+        );
+    }
 }
@@ -1,27 +1,39 @@
 use rapier3d::prelude::{
-    BroadPhaseMultiSap, CCDSolver, ColliderSet, DefaultBroadPhase, ImpulseJointSet,
-    IntegrationParameters, IslandManager, MultibodyJointSet, NarrowPhase, PhysicsPipeline,
-    RigidBodySet,
+    CCDSolver, ColliderSet, DefaultBroadPhase, ImpulseJointSet, IntegrationParameters,
+    IslandManager, MultibodyJointSet, NarrowPhase, PhysicsPipeline, QueryPipeline, RigidBodySet,
 };
 
+use crate::physics::{collision_layers::CollisionLayerTable, physics_config::PhysicsConfig};
+
 pub struct PhysicsWorld {
     pub physics_pipeline: PhysicsPipeline,
     pub integration_parameters: IntegrationParameters,
     pub islands: IslandManager,
-    pub broad_phase: BroadPhaseMultiSap,
+    // rapier 0.26 only exposes the multi-SAP broad phase, so there's nothing to choose between
+    pub broad_phase: DefaultBroadPhase,
     pub narrow_phase: NarrowPhase,
     pub bodies: RigidBodySet,
     pub colliders: ColliderSet,
     pub impulse_joints: ImpulseJointSet,
     pub multibody_joints: MultibodyJointSet,
     pub ccd_solver: CCDSolver,
+    pub query_pipeline: QueryPipeline,
+    pub collision_layers: CollisionLayerTable,
+    pub config: PhysicsConfig,
 }
 
 impl PhysicsWorld {
     pub fn new() -> Self {
+        Self::with_config(PhysicsConfig::load("src/assets/config/physics.cfg"))
+    }
+
+    pub fn with_config(config: PhysicsConfig) -> Self {
+        let mut integration_parameters = IntegrationParameters::default();
+        config.apply_to_integration_parameters(&mut integration_parameters);
+
         Self {
             physics_pipeline: PhysicsPipeline::new(),
-            integration_parameters: IntegrationParameters::default(),
+            integration_parameters,
             islands: IslandManager::new(),
             broad_phase: DefaultBroadPhase::new(),
             narrow_phase: NarrowPhase::new(),
@@ -30,6 +42,25 @@ impl PhysicsWorld {
             impulse_joints: ImpulseJointSet::new(),
             multibody_joints: MultibodyJointSet::new(),
             ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            collision_layers: CollisionLayerTable::load("src/assets/config/collision_layers.cfg"),
+            config,
+        }
+    }
+
+    // Push the current config into rapier, including bodies and colliders that already exist.
+    // CCD depends on each entity's own setting too, so ccd_system picks it up before the next
+    // step instead.
+    pub fn apply_config(&mut self) {
+        self.config
+            .apply_to_integration_parameters(&mut self.integration_parameters);
+
+        for (_handle, rb) in self.bodies.iter_mut() {
+            self.config.apply_to_activation(rb.activation_mut());
+        }
+
+        for (_handle, collider) in self.colliders.iter_mut() {
+            collider.set_contact_skin(self.config.contact_skin);
         }
     }
 }
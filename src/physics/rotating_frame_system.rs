@@ -0,0 +1,89 @@
+use glam::{Quat, Vec3};
+use hecs::{Entity, World};
+
+use crate::physics::{
+    physics_components::{Forces, MassProperties, RotatingFrame, Velocity},
+    transform::Transform,
+};
+
+struct FrameSnapshot {
+    entity: Entity,
+    origin: Vec3,
+    orientation: Quat,
+    velocity: Vec3,
+    omega: Vec3,
+    omega_dot: Vec3,
+    inner_radius: f32,
+    outer_radius: f32,
+    half_length: f32,
+    local_axis: Vec3,
+}
+
+impl FrameSnapshot {
+    fn contains(&self, position: Vec3) -> bool {
+        let local = self.orientation.inverse() * (position - self.origin);
+        let axial = local.dot(self.local_axis);
+        let radial = (local - self.local_axis * axial).length();
+
+        axial.abs() <= self.half_length
+            && radial >= self.inner_radius
+            && radial <= self.outer_radius
+    }
+}
+
+// Adds rotating-frame pseudo-forces to every body inside a RotatingFrame volume. A body inside
+// several volumes only feels the first. Must run before sync_ecs_to_rapier.
+pub fn rotating_frame_system(world: &mut World, dt: f32) {
+    // The spin change carries over to the next real step instead of dividing by zero here
+    if dt <= 0.0 {
+        return;
+    }
+
+    let frames: Vec<FrameSnapshot> = world
+        .query_mut::<(&mut RotatingFrame, &Transform, Option<&Velocity>)>()
+        .into_iter()
+        .map(|(entity, (frame, transform, velocity))| {
+            let axis = transform.orientation * frame.axis;
+            let omega_dot = axis * (frame.spin_rate - frame.prev_spin_rate) / dt;
+            frame.prev_spin_rate = frame.spin_rate;
+
+            FrameSnapshot {
+                entity,
+                origin: transform.position,
+                orientation: transform.orientation,
+                velocity: velocity.map_or(Vec3::ZERO, |velocity| velocity.linear),
+                omega: axis * frame.spin_rate,
+                omega_dot,
+                inner_radius: frame.inner_radius,
+                outer_radius: frame.outer_radius,
+                half_length: frame.half_length,
+                local_axis: frame.axis,
+            }
+        })
+        .collect();
+
+    if frames.is_empty() {
+        return;
+    }
+
+    for (entity, (transform, velocity, mass_properties, forces)) in
+        world.query_mut::<(&Transform, &Velocity, &MassProperties, &mut Forces)>()
+    {
+        let Some(frame) = frames
+            .iter()
+            .find(|frame| frame.entity != entity && frame.contains(transform.position))
+        else {
+            continue;
+        };
+
+        let mass = mass_properties.mass;
+        let r = transform.position - frame.origin;
+        let relative_velocity = velocity.linear - frame.velocity;
+
+        let centrifugal = -mass * frame.omega.cross(frame.omega.cross(r));
+        let coriolis = -2.0 * mass * frame.omega.cross(relative_velocity);
+        let euler = -mass * frame.omega_dot.cross(r);
+
+        forces.linear += centrifugal + coriolis + euler;
+    }
+}
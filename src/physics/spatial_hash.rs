@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use glam::{IVec3, Vec3};
+
+// Uniform grid over indices into some caller's list of points, for finding what is near what
+// without checking every pair
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, position: Vec3) {
+        self.cells
+            .entry(self.cell_of(position))
+            .or_default()
+            .push(index);
+    }
+
+    // Every index in the cells the sphere overlaps, which can include some just outside it
+    pub fn query(&self, position: Vec3, radius: f32, mut visit: impl FnMut(usize)) {
+        let min = self.cell_of(position - Vec3::splat(radius));
+        let max = self.cell_of(position + Vec3::splat(radius));
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    if let Some(indices) = self.cells.get(&IVec3::new(x, y, z)) {
+                        indices.iter().copied().for_each(&mut visit);
+                    }
+                }
+            }
+        }
+    }
+
+    fn cell_of(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size).floor().as_ivec3()
+    }
+}
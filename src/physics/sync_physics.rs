@@ -6,7 +6,12 @@ use rapier3d::{
 };
 
 use crate::physics::{
-    physics_components::{BoxCollider, Forces, InertiaProperties, MassProperties, Velocity},
+    ccd_system::ccd_enabled,
+    physics_components::{
+        BoxCollider, CollisionFilter, ContinuousCollision, Damping, FixedBody, ForceFrame, Forces,
+        InertiaProperties, MassProperties, PhysicsMaterial, PointForce, Velocity,
+    },
+    physics_hooks::encode_user_data,
     physics_world::PhysicsWorld,
     transform::Transform,
 };
@@ -15,8 +20,31 @@ pub fn sync_new_entities(world: &mut World, physics_world: &mut PhysicsWorld) {
     let mut new_entities = Vec::new();
 
     // Find entities with physics components but no RigidBodyHandle
-    for (entity, (transform, mass_properties, box_collider, velocity)) in world
-        .query::<(&Transform, &MassProperties, &BoxCollider, &Velocity)>()
+    for (
+        entity,
+        (
+            transform,
+            mass_properties,
+            box_collider,
+            velocity,
+            collision_filter,
+            ccd,
+            fixed,
+            material,
+            damping,
+        ),
+    ) in world
+        .query::<(
+            &Transform,
+            &MassProperties,
+            &BoxCollider,
+            &Velocity,
+            Option<&CollisionFilter>,
+            Option<&ContinuousCollision>,
+            Option<&FixedBody>,
+            Option<&PhysicsMaterial>,
+            Option<&Damping>,
+        )>()
         .without::<&RigidBodyHandle>() // Key filter!
         .iter()
     {
@@ -26,7 +54,15 @@ pub fn sync_new_entities(world: &mut World, physics_world: &mut PhysicsWorld) {
         let na_quat =
             UnitQuaternion::from_quaternion(Quaternion::new(quat.w, quat.x, quat.y, quat.z));
 
-        let rb = RigidBodyBuilder::dynamic()
+        let config = &physics_world.config;
+        let rb_builder = if fixed.is_some() {
+            RigidBodyBuilder::fixed()
+        } else {
+            RigidBodyBuilder::dynamic()
+        };
+
+        let speed = velocity.linear.length();
+        let mut rb = rb_builder
             .translation(vector![position.x, position.y, position.z])
             .rotation(na_quat.scaled_axis())
             .linvel(vector![
@@ -39,15 +75,46 @@ pub fn sync_new_entities(world: &mut World, physics_world: &mut PhysicsWorld) {
                 velocity.angular.y,
                 velocity.angular.z
             ])
+            .ccd_enabled(ccd_enabled(config, ccd, speed))
+            .soft_ccd_prediction(ccd.map_or(0.0, |ccd| ccd.soft_ccd_prediction))
+            .linear_damping(damping.map_or(0.0, |damping| damping.linear))
+            .angular_damping(damping.map_or(0.0, |damping| damping.angular))
             .build();
+        config.apply_to_activation(rb.activation_mut());
 
-        let collider = ColliderBuilder::cuboid(
+        let mut collider_builder = ColliderBuilder::cuboid(
             box_collider.extents.x / 2.0,
             box_collider.extents.y / 2.0,
             box_collider.extents.z / 2.0,
         )
         .mass(mass_properties.mass)
-        .build();
+        .contact_skin(config.contact_skin)
+        .user_data(encode_user_data(
+            entity,
+            collision_filter.and_then(|filter| filter.owner),
+        ));
+
+        if let Some(filter) = collision_filter {
+            let layers = &physics_world.collision_layers;
+            collider_builder = collider_builder
+                .collision_groups(layers.interaction_groups(filter.layer))
+                .solver_groups(layers.solver_groups(filter.layer));
+        }
+
+        if let Some(material) = material {
+            collider_builder = collider_builder
+                .friction(material.friction)
+                .restitution(material.restitution)
+                .friction_combine_rule(material.friction_combine_rule)
+                .restitution_combine_rule(material.restitution_combine_rule);
+
+            if let Some(density) = material.density {
+                collider_builder = collider_builder.density(density);
+            }
+        }
+
+        let collider = collider_builder.build();
+        let mass_properties = MassProperties::new(collider.mass());
 
         let inertia_tensor = collider.mass_properties().principal_inertia();
         let inertia_mat = Mat3::from_diagonal(Vec3::new(
@@ -64,17 +131,44 @@ pub fn sync_new_entities(world: &mut World, physics_world: &mut PhysicsWorld) {
             &mut physics_world.bodies,
         );
 
-        new_entities.push((entity, rb_handle, collider_handle, inertia_properties));
+        new_entities.push((
+            entity,
+            rb_handle,
+            collider_handle,
+            inertia_properties,
+            mass_properties,
+        ));
     }
 
-    // Insert handles into entities
-    for (entity, rb_handle, collider_handle, inertia_properties) in new_entities {
+    // Insert handles into entities, MassProperties is replaced in case density decided the mass
+    for (entity, rb_handle, collider_handle, inertia_properties, mass_properties) in new_entities {
         world
-            .insert(entity, (rb_handle, collider_handle, inertia_properties))
+            .insert(
+                entity,
+                (
+                    rb_handle,
+                    collider_handle,
+                    inertia_properties,
+                    mass_properties,
+                ),
+            )
             .expect("Entity should exist");
     }
 }
 
+// Removes a body along with its attached colliders and joints. Call before despawning an entity
+// that has a RigidBodyHandle, otherwise the body is left behind in the simulation.
+pub fn remove_rigid_body(physics_world: &mut PhysicsWorld, rb_handle: RigidBodyHandle) {
+    physics_world.bodies.remove(
+        rb_handle,
+        &mut physics_world.islands,
+        &mut physics_world.colliders,
+        &mut physics_world.impulse_joints,
+        &mut physics_world.multibody_joints,
+        true,
+    );
+}
+
 pub fn sync_ecs_to_rapier(world: &World, physics_world: &mut PhysicsWorld) {
     for (_entity, (forces, rb_handle)) in world.query::<(&Forces, &RigidBodyHandle)>().iter() {
         if let Some(rb) = physics_world.bodies.get_mut(*rb_handle) {
@@ -86,6 +180,16 @@ pub fn sync_ecs_to_rapier(world: &World, physics_world: &mut PhysicsWorld) {
                 vector![forces.torque.x, forces.torque.y, forces.torque.z],
                 true,
             );
+
+            for point_force in &forces.point_forces {
+                let (force, point) = to_world_space(rb, point_force);
+                rb.add_force_at_point(force, point, true);
+            }
+
+            for point_impulse in &forces.point_impulses {
+                let (impulse, point) = to_world_space(rb, point_impulse);
+                rb.apply_impulse_at_point(impulse, point, true);
+            }
         }
     }
 }
@@ -107,8 +211,28 @@ pub fn sync_rapier_to_ecs(world: &mut World, physics_world: &mut PhysicsWorld) {
 
             rb.reset_forces(true);
             rb.reset_torques(true);
-            forces.linear = Vec3::ZERO;
-            forces.torque = Vec3::ZERO;
+            forces.clear();
         }
     }
 }
+
+pub fn to_world_space(rb: &RigidBody, point_force: &PointForce) -> (Vector<Real>, Point<Real>) {
+    let vector = vector![
+        point_force.vector.x,
+        point_force.vector.y,
+        point_force.vector.z
+    ];
+    let point = point![
+        point_force.point.x,
+        point_force.point.y,
+        point_force.point.z
+    ];
+
+    match point_force.frame {
+        ForceFrame::World => (vector, point),
+        ForceFrame::Local => (
+            rb.rotation() * vector,
+            rb.center_of_mass() + rb.rotation() * point.coords,
+        ),
+    }
+}
@@ -0,0 +1,50 @@
+use glam::{Vec3, Vec4};
+
+use crate::render::vertex::LineVertex;
+
+pub const MAX_DEBUG_LINE_VERTICES: usize = 200_000;
+
+// Immediate-mode line list, rebuilt every frame and drawn on top of the scene
+pub struct DebugLines {
+    pub vertices: Vec<LineVertex>,
+}
+
+impl DebugLines {
+    pub fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+        }
+    }
+
+    pub fn line(&mut self, a: Vec3, b: Vec3, color: Vec4) {
+        if self.vertices.len() + 2 > MAX_DEBUG_LINE_VERTICES {
+            return;
+        }
+
+        self.vertices.push(LineVertex { position: a, color });
+        self.vertices.push(LineVertex { position: b, color });
+    }
+
+    pub fn arrow(&mut self, origin: Vec3, vector: Vec3, color: Vec4) {
+        let length = vector.length();
+        if length < 1e-3 {
+            return;
+        }
+
+        let tip = origin + vector;
+        self.line(origin, tip, color);
+
+        // Four head segments swept back from the tip
+        let direction = vector / length;
+        let (side, up) = direction.any_orthonormal_pair();
+        let head_length = (length * 0.2).min(1.0);
+        let back = tip - direction * head_length;
+        for offset in [side, -side, up, -up] {
+            self.line(tip, back + offset * head_length * 0.5, color);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+}
@@ -0,0 +1,109 @@
+use glam::{Vec2, Vec4};
+
+use crate::render::debug_lines::DebugLines;
+
+// Pixels per glyph grid unit. Glyphs sit on a 4 x 6 grid with 2 units between characters.
+const GLYPH_SCALE: f32 = 2.0;
+const GLYPH_ADVANCE: f32 = 6.0 * GLYPH_SCALE;
+pub const LINE_HEIGHT: f32 = 9.0 * GLYPH_SCALE;
+
+// Screen-space text drawn as line strokes, rebuilt every frame and drawn over everything else.
+// Positions are in pixels from the window's top-left corner.
+pub struct Hud {
+    pub lines: DebugLines,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Self {
+            lines: DebugLines::new(),
+        }
+    }
+
+    // One line of text with its top-left corner at `position`. Letters are drawn upper case.
+    pub fn text(&mut self, position: Vec2, text: &str, color: Vec4) {
+        for (index, character) in text.chars().enumerate() {
+            let origin = position + Vec2::X * GLYPH_ADVANCE * index as f32;
+            for stroke in glyph(character.to_ascii_uppercase()).split(';') {
+                let points: Vec<Vec2> = stroke
+                    .split_whitespace()
+                    .map(|point| {
+                        let grid = point.as_bytes();
+                        let x = (grid[0] - b'0') as f32;
+                        let y = (grid[1] - b'0') as f32;
+                        origin + Vec2::new(x, y) * GLYPH_SCALE
+                    })
+                    .collect();
+
+                for pair in points.windows(2) {
+                    self.lines
+                        .line(pair[0].extend(0.0), pair[1].extend(0.0), color);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+// Strokes for each character as polylines split by ';', each point a column (0-4) and a row
+// (0-6, top down)
+fn glyph(character: char) -> &'static str {
+    match character {
+        'A' => "06 01 10 30 41 46;03 43",
+        'B' => "06 00 30 41 42 33 03;33 44 45 36 06",
+        'C' => "41 30 10 01 05 16 36 45",
+        'D' => "00 06 26 44 42 20 00",
+        'E' => "40 00 06 46;03 33",
+        'F' => "40 00 06;03 33",
+        'G' => "41 30 10 01 05 16 36 45 43 23",
+        'H' => "00 06;40 46;03 43",
+        'I' => "10 30;20 26;16 36",
+        'J' => "40 45 36 16 05",
+        'K' => "00 06;40 03 46",
+        'L' => "00 06 46",
+        'M' => "06 00 23 40 46",
+        'N' => "06 00 46 40",
+        'O' => "10 30 41 45 36 16 05 01 10",
+        'P' => "06 00 30 41 42 33 03",
+        'Q' => "10 30 41 45 36 16 05 01 10;24 46",
+        'R' => "06 00 30 41 42 33 03;23 46",
+        'S' => "41 30 10 01 02 13 33 44 45 36 16 05",
+        'T' => "00 40;20 26",
+        'U' => "00 05 16 36 45 40",
+        'V' => "00 26 40",
+        'W' => "00 16 23 36 40",
+        'X' => "00 46;40 06",
+        'Y' => "00 23 40;23 26",
+        'Z' => "00 40 06 46",
+        '0' => "10 30 41 45 36 16 05 01 10;41 05",
+        '1' => "11 20 26;16 36",
+        '2' => "01 10 30 41 42 06 46",
+        '3' => "00 40 23 33 44 45 36 16 05",
+        '4' => "43 03 30 36",
+        '5' => "40 00 02 32 43 45 36 06",
+        '6' => "30 10 01 05 16 36 45 43 32 02",
+        '7' => "00 40 42 26",
+        '8' => "10 30 41 42 33 13 02 01 10;13 04 05 16 36 45 44 33",
+        '9' => "43 13 02 01 10 30 41 45 36 16",
+        ':' => "21 22;24 25",
+        '.' => "25 26",
+        ',' => "25 16",
+        '\'' => "20 21",
+        '-' => "13 33",
+        '+' => "13 33;22 24",
+        '=' => "12 32;14 34",
+        '_' => "06 46",
+        '/' => "06 40",
+        '%' => "06 40;00 01;45 46",
+        '>' => "10 33 16",
+        '<' => "30 03 36",
+        '(' => "30 21 25 36",
+        ')' => "10 21 25 16",
+        ' ' => "",
+        // Anything else shows as a box so it's obvious a glyph is missing
+        _ => "00 40 46 06 00",
+    }
+}
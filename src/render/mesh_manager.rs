@@ -19,6 +19,21 @@ impl MeshID {
 
 pub const MAX_INSTANCES: usize = 10_000;
 
+pub enum FractureSource {
+    // One piece per `o`/`g` group authored in the OBJ file
+    ObjGroups,
+    // Cut by axis-aligned planes through the bounding box centre along the longest
+    // `n` axes (at most 3), giving up to 2^n pieces
+    PlaneSlices(u32),
+}
+
+pub struct MeshFragment {
+    pub mesh_id: MeshID,
+    // Centre of the fragment's bounding box in the source mesh's space
+    pub center: Vec3,
+    pub extents: Vec3,
+}
+
 pub struct MeshManager {
     pub mesh_batches: HashMap<MeshID, MeshBatch>,
     mesh_id_lookup: HashMap<String, MeshID>,
@@ -37,6 +52,34 @@ impl MeshManager {
 
     pub fn register_mesh(&mut self, ctx: &mut Box<dyn RenderingBackend>, filepath: &str) -> MeshID {
         let mesh = load_obj(filepath);
+        let id = self.upload_mesh(ctx, &mesh);
+        self.mesh_id_lookup.insert(filepath.to_string(), id);
+
+        id
+    }
+
+    // Splits a mesh into separately drawable pieces, each re-centred on its own bounding box so
+    // it can be spawned as an independent body. Fragments are registered as "<filepath>#<n>".
+    pub fn register_fragments(
+        &mut self,
+        ctx: &mut Box<dyn RenderingBackend>,
+        filepath: &str,
+        source: FractureSource,
+    ) -> Vec<MeshFragment> {
+        let mut fragments = Vec::new();
+        for (index, (mesh, mut fragment)) in
+            load_fragments(filepath, source).into_iter().enumerate()
+        {
+            fragment.mesh_id = self.upload_mesh(ctx, &mesh);
+            self.mesh_id_lookup
+                .insert(format!("{}#{}", filepath, index), fragment.mesh_id);
+            fragments.push(fragment);
+        }
+
+        fragments
+    }
+
+    fn upload_mesh(&mut self, ctx: &mut Box<dyn RenderingBackend>, mesh: &Mesh) -> MeshID {
         let id = MeshID(self.next_mesh_id);
         self.next_mesh_id += 1;
 
@@ -70,7 +113,6 @@ impl MeshManager {
         };
 
         self.mesh_batches.insert(id, batch);
-        self.mesh_id_lookup.insert(filepath.to_string(), id);
 
         id
     }
@@ -98,28 +140,124 @@ impl MeshManager {
         }
     }
 
-    pub fn get_mesh_id(&mut self, filepath: &str) -> MeshID {
-        if let Some(&id) = self.mesh_id_lookup.get(filepath) {
-            return id;
-        }
-
-        return MeshID::INVALID;
-    }
-
     pub fn iter_batches(&self) -> impl Iterator<Item = &MeshBatch> {
         self.mesh_batches.values()
     }
+}
+
+pub fn load_obj(path: &str) -> Mesh {
+    parse_obj(path).0
+}
+
+// The pieces register_fragments uploads, with their meshes already re-centred. The fragments'
+// mesh ids are INVALID until uploaded.
+pub fn load_fragments(filepath: &str, source: FractureSource) -> Vec<(Mesh, MeshFragment)> {
+    let pieces = match source {
+        FractureSource::ObjGroups => load_obj_groups(filepath),
+        FractureSource::PlaneSlices(axis_count) => slice_mesh(&load_obj(filepath), axis_count),
+    };
+
+    pieces
+        .into_iter()
+        .map(|mut mesh| {
+            let (min, max) = mesh_bounds(&mesh);
+            let center = (min + max) * 0.5;
+            for vertex in mesh.vertices.iter_mut() {
+                vertex.position -= center;
+            }
 
-    pub fn iter_batches_mut(&mut self) -> impl Iterator<Item = &mut MeshBatch> {
-        self.mesh_batches.values_mut()
+            let fragment = MeshFragment {
+                mesh_id: MeshID::INVALID,
+                center,
+                extents: max - min,
+            };
+            (mesh, fragment)
+        })
+        .collect()
+}
+
+fn load_obj_groups(path: &str) -> Vec<Mesh> {
+    let (mesh, group_starts) = parse_obj(path);
+
+    let mut bounds = group_starts.clone();
+    bounds.push(mesh.indices.len());
+
+    bounds
+        .windows(2)
+        .filter(|range| range[1] > range[0])
+        .map(|range| extract_submesh(&mesh, &mesh.indices[range[0]..range[1]]))
+        .collect()
+}
+
+// Assigns each triangle to a cell by which side of each cutting plane its centroid falls on.
+// Triangles aren't split, so the cut edges follow the original tessellation.
+fn slice_mesh(mesh: &Mesh, axis_count: u32) -> Vec<Mesh> {
+    let (min, max) = mesh_bounds(mesh);
+    let center = (min + max) * 0.5;
+    let size = max - min;
+
+    let mut axes = [0, 1, 2];
+    axes.sort_by(|&a, &b| size[b].total_cmp(&size[a]));
+    let axes = &axes[..axis_count.min(3) as usize];
+
+    let mut cells: Vec<Vec<u32>> = vec![Vec::new(); 1 << axes.len()];
+    for triangle in mesh.indices.chunks_exact(3) {
+        let centroid = triangle
+            .iter()
+            .map(|&i| mesh.vertices[i as usize].position)
+            .sum::<Vec3>()
+            / 3.0;
+
+        let cell = axes
+            .iter()
+            .enumerate()
+            .filter(|&(_, &axis)| centroid[axis] > center[axis])
+            .fold(0, |cell, (bit, _)| cell | (1 << bit));
+
+        cells[cell].extend_from_slice(triangle);
     }
+
+    cells
+        .iter()
+        .filter(|indices| !indices.is_empty())
+        .map(|indices| extract_submesh(mesh, indices))
+        .collect()
+}
+
+// Copies out only the vertices referenced by `indices`, remapping them to the new mesh
+fn extract_submesh(mesh: &Mesh, indices: &[u32]) -> Mesh {
+    let mut remap: HashMap<u32, u32> = HashMap::new();
+    let mut vertices = Vec::new();
+
+    let indices = indices
+        .iter()
+        .map(|&index| {
+            *remap.entry(index).or_insert_with(|| {
+                vertices.push(Vertex {
+                    position: mesh.vertices[index as usize].position,
+                });
+                vertices.len() as u32 - 1
+            })
+        })
+        .collect();
+
+    Mesh { vertices, indices }
 }
 
-fn load_obj(path: &str) -> Mesh {
+fn mesh_bounds(mesh: &Mesh) -> (Vec3, Vec3) {
+    mesh.vertices.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), vertex| (min.min(vertex.position), max.max(vertex.position)),
+    )
+}
+
+// Returns the mesh plus the index offset at which each `o`/`g` group starts
+fn parse_obj(path: &str) -> (Mesh, Vec<usize>) {
     let text = read_to_string(path).unwrap();
 
     let mut vertices: Vec<Vertex> = Vec::new();
     let mut indices: Vec<u32> = Vec::new();
+    let mut group_starts: Vec<usize> = Vec::new();
 
     for line in text.lines() {
         let line = line.trim();
@@ -156,15 +294,21 @@ fn load_obj(path: &str) -> Mesh {
 
                 // Fan triangulation
                 for i in 1..face.len() - 1 {
-                    indices.push(face[0] as u32);
-                    indices.push(face[i] as u32);
-                    indices.push(face[i + 1] as u32);
+                    indices.push(face[0]);
+                    indices.push(face[i]);
+                    indices.push(face[i + 1]);
                 }
             }
 
+            "o" | "g" => group_starts.push(indices.len()),
+
             _ => {} // ignore everything else
         }
     }
 
-    Mesh { vertices, indices }
+    if group_starts.first() != Some(&0) {
+        group_starts.insert(0, 0);
+    }
+
+    (Mesh { vertices, indices }, group_starts)
 }
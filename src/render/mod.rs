@@ -1,6 +1,9 @@
 pub mod camera;
+pub mod debug_lines;
+pub mod hud;
 pub mod mesh_batch;
 pub mod mesh_manager;
+pub mod physics_debug_overlay;
 pub mod render_components;
 pub mod renderer;
 pub mod shader;
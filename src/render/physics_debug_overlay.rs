@@ -0,0 +1,134 @@
+use glam::{Vec3, Vec4};
+use hecs::World;
+use rapier3d::prelude::*;
+
+use crate::{
+    physics::{
+        physics_components::Forces, physics_world::PhysicsWorld, sync_physics::to_world_space,
+    },
+    render::debug_lines::DebugLines,
+};
+
+// Draws rapier's view of the world (collider wireframes, AABBs, contacts and normals, joint
+// anchors) plus per-entity velocity, force and torque arrows. Sleeping bodies are drawn
+// darker through rapier's sleep colour multiplier.
+pub struct PhysicsDebugOverlay {
+    pub enabled: bool,
+    pub lines: DebugLines,
+    pipeline: DebugRenderPipeline,
+
+    // Arrow length per unit of the drawn quantity
+    pub velocity_scale: f32,
+    pub force_scale: f32,
+    pub torque_scale: f32,
+}
+
+impl PhysicsDebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            lines: DebugLines::new(),
+            pipeline: DebugRenderPipeline::new(
+                DebugRenderStyle::default(),
+                DebugRenderMode::COLLIDER_SHAPES
+                    | DebugRenderMode::COLLIDER_AABBS
+                    | DebugRenderMode::CONTACTS
+                    | DebugRenderMode::JOINTS,
+            ),
+            velocity_scale: 0.5,
+            force_scale: 1e-4,
+            torque_scale: 1e-4,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.lines.clear();
+    }
+
+    // Must run after the physics step but before sync_rapier_to_ecs clears Forces
+    pub fn collect(&mut self, world: &World, physics_world: &PhysicsWorld) {
+        self.lines.clear();
+        if !self.enabled {
+            return;
+        }
+
+        self.pipeline.render(
+            &mut LineCollector(&mut self.lines),
+            &physics_world.bodies,
+            &physics_world.colliders,
+            &physics_world.impulse_joints,
+            &physics_world.multibody_joints,
+            &physics_world.narrow_phase,
+        );
+
+        let velocity_color = Vec4::new(0.2, 1.0, 0.2, 1.0);
+        let force_color = Vec4::new(1.0, 0.2, 0.2, 1.0);
+        let point_force_color = Vec4::new(1.0, 0.6, 0.1, 1.0);
+        let torque_color = Vec4::new(0.3, 0.5, 1.0, 1.0);
+
+        for (_entity, (forces, rb_handle)) in world.query::<(&Forces, &RigidBodyHandle)>().iter() {
+            let Some(rb) = physics_world.bodies.get(*rb_handle) else {
+                continue;
+            };
+
+            let center = to_vec3(rb.center_of_mass().coords);
+
+            self.lines.arrow(
+                center,
+                to_vec3(*rb.linvel()) * self.velocity_scale,
+                velocity_color,
+            );
+            self.lines
+                .arrow(center, forces.linear * self.force_scale, force_color);
+            self.lines
+                .arrow(center, forces.torque * self.torque_scale, torque_color);
+
+            for point_force in forces.point_forces.iter().chain(&forces.point_impulses) {
+                let (vector, point) = to_world_space(rb, point_force);
+
+                self.lines.arrow(
+                    to_vec3(point.coords),
+                    to_vec3(vector) * self.force_scale,
+                    point_force_color,
+                );
+            }
+        }
+    }
+}
+
+struct LineCollector<'a>(&'a mut DebugLines);
+
+impl DebugRenderBackend for LineCollector<'_> {
+    fn draw_line(
+        &mut self,
+        _object: DebugRenderObject,
+        a: Point<Real>,
+        b: Point<Real>,
+        color: DebugColor,
+    ) {
+        self.0
+            .line(to_vec3(a.coords), to_vec3(b.coords), hsla_to_rgba(color));
+    }
+}
+
+fn to_vec3(v: Vector<Real>) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
+
+// rapier's debug colours are HSLA with hue in degrees
+fn hsla_to_rgba([h, s, l, a]: DebugColor) -> Vec4 {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = (h % 360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    Vec4::new(r + m, g + m, b + m, a)
+}
@@ -2,13 +2,23 @@ use glam::{Mat4, Vec3, Vec4};
 use miniquad::*;
 use rand::Rng;
 
-use crate::render::{mesh_batch::Instance, mesh_manager::MeshManager, shader::*, vertex::Vertex};
+use crate::render::{
+    debug_lines::{DebugLines, MAX_DEBUG_LINE_VERTICES},
+    mesh_batch::Instance,
+    mesh_manager::MeshManager,
+    shader::*,
+    vertex::{LineVertex, Vertex},
+};
 
 pub struct Renderer {
     solid_pipeline: Pipeline,
     wireframe_pipeline: Pipeline,
     starfield_pipeline: Pipeline,
     starfield_bindings: Bindings,
+    debug_line_pipeline: Pipeline,
+    debug_line_bindings: Bindings,
+    hud_pipeline: Pipeline,
+    hud_bindings: Bindings,
 }
 
 impl Renderer {
@@ -146,11 +156,98 @@ impl Renderer {
             images: vec![],
         };
 
+        // Debug line pipeline setup
+        let debug_line_params = PipelineParams {
+            depth_test: Comparison::LessOrEqual,
+            depth_write: false,
+            primitive_type: PrimitiveType::Lines,
+            ..Default::default()
+        };
+
+        let debug_line_shader = ctx
+            .new_shader(
+                ShaderSource::Glsl {
+                    vertex: &load_shader("src/assets/shaders/debug_line_vert.glsl"),
+                    fragment: &load_shader("src/assets/shaders/debug_line_frag.glsl"),
+                },
+                debug_line_shader_meta(),
+            )
+            .unwrap();
+
+        let debug_line_vertex_buffer = ctx.new_buffer(
+            BufferType::VertexBuffer,
+            BufferUsage::Stream,
+            BufferSource::empty::<LineVertex>(MAX_DEBUG_LINE_VERTICES),
+        );
+
+        // Lines are drawn as an unindexed list, so the index buffer is just 0..N
+        let debug_line_indices: Vec<u32> = (0..MAX_DEBUG_LINE_VERTICES as u32).collect();
+        let debug_line_index_buffer = ctx.new_buffer(
+            BufferType::IndexBuffer,
+            BufferUsage::Immutable,
+            BufferSource::slice(&debug_line_indices),
+        );
+
+        let debug_line_buffer_layout = BufferLayout {
+            stride: std::mem::size_of::<LineVertex>() as i32,
+            step_func: VertexStep::PerVertex,
+            ..Default::default()
+        };
+
+        let debug_line_attributes = vec![
+            VertexAttribute::new("in_pos", VertexFormat::Float3),
+            VertexAttribute::new("in_color", VertexFormat::Float4),
+        ];
+
+        let debug_line_pipeline = ctx.new_pipeline(
+            std::slice::from_ref(&debug_line_buffer_layout),
+            &debug_line_attributes,
+            debug_line_shader,
+            debug_line_params,
+        );
+
+        let debug_line_bindings = Bindings {
+            vertex_buffers: vec![debug_line_vertex_buffer],
+            index_buffer: debug_line_index_buffer,
+            images: vec![],
+        };
+
+        // HUD pipeline, the same lines in screen space drawn over everything
+        let hud_params = PipelineParams {
+            depth_test: Comparison::Always,
+            depth_write: false,
+            primitive_type: PrimitiveType::Lines,
+            ..Default::default()
+        };
+
+        let hud_pipeline = ctx.new_pipeline(
+            &[debug_line_buffer_layout],
+            &debug_line_attributes,
+            debug_line_shader,
+            hud_params,
+        );
+
+        let hud_vertex_buffer = ctx.new_buffer(
+            BufferType::VertexBuffer,
+            BufferUsage::Stream,
+            BufferSource::empty::<LineVertex>(MAX_DEBUG_LINE_VERTICES),
+        );
+
+        let hud_bindings = Bindings {
+            vertex_buffers: vec![hud_vertex_buffer],
+            index_buffer: debug_line_index_buffer,
+            images: vec![],
+        };
+
         Self {
             solid_pipeline,
             wireframe_pipeline,
             starfield_pipeline,
             starfield_bindings,
+            debug_line_pipeline,
+            debug_line_bindings,
+            hud_pipeline,
+            hud_bindings,
         }
     }
 
@@ -167,6 +264,45 @@ impl Renderer {
         mesh_manager.clear_instance_buffer();
     }
 
+    pub fn draw_debug_lines(
+        &mut self,
+        ctx: &mut Box<dyn RenderingBackend>,
+        debug_lines: &DebugLines,
+        view_proj: Mat4,
+    ) {
+        if debug_lines.vertices.is_empty() {
+            return;
+        }
+
+        ctx.buffer_update(
+            self.debug_line_bindings.vertex_buffers[0],
+            BufferSource::slice(&debug_lines.vertices),
+        );
+        ctx.apply_pipeline(&self.debug_line_pipeline);
+        ctx.apply_bindings(&self.debug_line_bindings);
+        ctx.apply_uniforms(UniformsSource::table(&view_proj));
+        ctx.draw(0, debug_lines.vertices.len() as i32, 1);
+    }
+
+    // `hud_lines` are in pixels from the top-left corner of the window
+    pub fn draw_hud(&mut self, ctx: &mut Box<dyn RenderingBackend>, hud_lines: &DebugLines) {
+        if hud_lines.vertices.is_empty() {
+            return;
+        }
+
+        let (width, height) = window::screen_size();
+        let screen_proj = Mat4::orthographic_rh_gl(0.0, width, height, 0.0, -1.0, 1.0);
+
+        ctx.buffer_update(
+            self.hud_bindings.vertex_buffers[0],
+            BufferSource::slice(&hud_lines.vertices),
+        );
+        ctx.apply_pipeline(&self.hud_pipeline);
+        ctx.apply_bindings(&self.hud_bindings);
+        ctx.apply_uniforms(UniformsSource::table(&screen_proj));
+        ctx.draw(0, hud_lines.vertices.len() as i32, 1);
+    }
+
     fn draw_starfield(&self, ctx: &mut Box<dyn RenderingBackend>, view_proj: Mat4) {
         ctx.apply_pipeline(&self.starfield_pipeline);
         ctx.apply_bindings(&self.starfield_bindings);
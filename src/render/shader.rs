@@ -2,7 +2,7 @@ use ::std::fs;
 use miniquad::*;
 
 pub fn load_shader(path: &str) -> String {
-    fs::read_to_string(path).expect(&format!("Failed to read shader: {}", path))
+    fs::read_to_string(path).unwrap_or_else(|_| panic!("Failed to read shader: {}", path))
 }
 
 pub fn starfield_shader_meta() -> ShaderMeta {
@@ -25,3 +25,12 @@ pub fn geometry_shader_meta() -> ShaderMeta {
         images: vec![],
     }
 }
+
+pub fn debug_line_shader_meta() -> ShaderMeta {
+    ShaderMeta {
+        uniforms: UniformBlockLayout {
+            uniforms: vec![UniformDesc::new("view_proj", UniformType::Mat4)],
+        },
+        images: vec![],
+    }
+}
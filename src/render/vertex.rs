@@ -1,4 +1,4 @@
-use glam::Vec3;
+use glam::{Vec3, Vec4};
 
 #[repr(C)]
 pub struct Vertex {
@@ -9,3 +9,9 @@ pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
 }
+
+#[repr(C)]
+pub struct LineVertex {
+    pub position: Vec3,
+    pub color: Vec4,
+}
@@ -0,0 +1,164 @@
+use std::f32;
+
+use glam::{Quat, Vec3};
+use hecs::World;
+
+use crate::{
+    core::simulation::step_physics,
+    destruction::destruction_components::{Debris, DebrisPiece, Destructible, HullIntegrity},
+    physics::{
+        physics_components::{BoxCollider, Forces, MassProperties, Velocity},
+        physics_world::PhysicsWorld,
+        transform::Transform,
+    },
+    render::{
+        mesh_manager::{FractureSource, MeshFragment, MeshID, load_fragments, load_obj},
+        vertex::Mesh,
+    },
+    tests::{HULL, spawn_body},
+};
+
+// A spinning, drifting hull broken into two halves should leave exactly two bodies behind,
+// each flying apart and keeping the parent's spin
+#[test]
+fn fracture() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let half = |offset: f32| DebrisPiece {
+        mesh_id: MeshID::INVALID,
+        local_offset: Vec3::new(offset, 0.0, 0.0),
+        extents: Vec3::new(2.0, 1.0, 1.0),
+    };
+
+    let hull = world.spawn((
+        Transform {
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        MassProperties::new(1000.0),
+        BoxCollider::new(4.0, 1.0, 1.0),
+        Velocity {
+            linear: Vec3::new(0.0, 0.0, 10.0),
+            angular: Vec3::new(0.0, 1.0, 0.0),
+        },
+        Forces::ZERO,
+        HullIntegrity::new(100.0),
+        Destructible::new(vec![half(-1.0), half(1.0)], 2.0),
+    ));
+
+    step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+    world
+        .get::<&mut HullIntegrity>(hull)
+        .expect("Hull should exist")
+        .apply_damage(100.0);
+    step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+
+    let pieces: Vec<(Vec3, Vec3)> = world
+        .query::<(&Velocity, &Debris)>()
+        .iter()
+        .map(|(_entity, (velocity, _debris))| (velocity.linear, velocity.angular))
+        .collect();
+
+    // The halves move apart along x, and keep the parent's drift give or take the spin
+    let separation_speed = pieces
+        .iter()
+        .map(|(linear, _angular)| linear.x.abs())
+        .fold(f32::MAX, f32::min);
+
+    println!(
+        "fracture: {} pieces, {} rigid bodies, hull despawned: {}, pieces {:?}",
+        pieces.len(),
+        physics_world.bodies.len(),
+        !world.contains(hull),
+        pieces
+    );
+
+    assert!(
+        !world.contains(hull)
+            && pieces.len() == 2
+            && physics_world.bodies.len() == 2
+            && separation_speed > 1.0
+            && pieces
+                .iter()
+                .all(|(linear, angular)| (linear.z - 10.0).abs() < 1.5 && angular.y > 0.9)
+    );
+}
+
+// The albatross broken up by its OBJ groups and by two and three cutting planes. Moved back to
+// where they came from, the pieces' triangles should enclose the hull's volume, and the debris
+// spawned from them should weigh what the hull did between them.
+#[test]
+fn fracture_mesh() {
+    let path = "src/assets/meshes/albatross.obj";
+    let hull_volume = mesh_volume(&load_obj(path), Vec3::ZERO);
+
+    for (source, expected_pieces) in [
+        (FractureSource::ObjGroups, 3),
+        (FractureSource::PlaneSlices(2), 4),
+        (FractureSource::PlaneSlices(3), 8),
+    ] {
+        let (meshes, fragments): (Vec<Mesh>, Vec<MeshFragment>) =
+            load_fragments(path, source).into_iter().unzip();
+        let volume: f32 = meshes
+            .iter()
+            .zip(&fragments)
+            .map(|(mesh, fragment)| mesh_volume(mesh, fragment.center))
+            .sum();
+
+        let mut world = World::new();
+        let mut physics_world = PhysicsWorld::new();
+        let hull = spawn_body(&mut world, Vec3::ZERO, 5000.0, HULL, Vec3::ZERO);
+        world
+            .insert(
+                hull,
+                (
+                    HullIntegrity::new(100.0),
+                    Destructible::from_fragments(&fragments, 2.0),
+                ),
+            )
+            .expect("Hull should exist");
+
+        step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+        world
+            .get::<&mut HullIntegrity>(hull)
+            .expect("Hull should exist")
+            .apply_damage(100.0);
+        step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+
+        let masses: Vec<f32> = world
+            .query::<(&MassProperties, &Debris)>()
+            .iter()
+            .map(|(_entity, (mass_properties, _debris))| mass_properties.mass)
+            .collect();
+        let mass: f32 = masses.iter().sum();
+
+        println!(
+            "fracture-mesh: {} pieces with volume {:.3} of the hull's {:.3}, debris mass {:.2}",
+            fragments.len(),
+            volume,
+            hull_volume,
+            mass
+        );
+
+        assert!(
+            fragments.len() == expected_pieces
+                && masses.len() == expected_pieces
+                && (volume - hull_volume).abs() < hull_volume.abs() * 1e-3
+                && (mass - 5000.0).abs() < 0.5
+        );
+    }
+}
+
+// Signed volume enclosed by a mesh's triangles, with its vertices moved by `offset`
+fn mesh_volume(mesh: &Mesh, offset: Vec3) -> f32 {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let [a, b, c] =
+                [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].position + offset);
+            a.dot(b.cross(c)) / 6.0
+        })
+        .sum()
+}
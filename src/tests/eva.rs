@@ -0,0 +1,170 @@
+use glam::{Quat, Vec2, Vec3};
+use hecs::World;
+use rapier3d::prelude::RigidBodyHandle;
+
+use crate::{
+    core::simulation::step_physics,
+    eva::{
+        eva_components::{Airlock, Boarded, EvaCharacter, EvaInput},
+        eva_system::{board_ship, exit_ship},
+    },
+    physics::{
+        physics_components::{BoxCollider, Forces, MassProperties, Velocity},
+        physics_world::PhysicsWorld,
+        sync_physics::remove_rigid_body,
+        transform::Transform,
+    },
+};
+
+use crate::tests::{HULL, spawn_body};
+
+// An astronaut next to an airlock should board the ship and come back out at the airlock. If
+// the ship is destroyed while they are aboard, leaving should still work and drop them where
+// the ship was instead of keeping them boarded for good.
+#[test]
+fn eva_airlock() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let ship_position = Vec3::new(0.0, 0.0, 0.0);
+    let airlock_offset = Vec3::new(0.0, 2.0, 0.0);
+    let ship = spawn_body(&mut world, ship_position, 5000.0, HULL, Vec3::ZERO);
+    world
+        .insert_one(ship, Airlock::new(airlock_offset, 3.0))
+        .expect("Ship should exist");
+    let astronaut = world.spawn((
+        Transform {
+            position: Vec3::new(0.0, 3.0, 1.0),
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        EvaCharacter::new(0.6, 0.3, 100.0),
+        EvaInput {
+            local_move: Vec3::ZERO,
+            local_rotation: Quat::IDENTITY,
+        },
+    ));
+
+    let dt = 1.0 / 60.0;
+    step_physics(&mut world, &mut physics_world, dt);
+    let boarded = board_ship(&mut world, &mut physics_world, astronaut);
+    step_physics(&mut world, &mut physics_world, dt);
+    let exited = exit_ship(&mut world, astronaut);
+    let exit_position = world
+        .get::<&Transform>(astronaut)
+        .expect("Astronaut should exist")
+        .position;
+
+    step_physics(&mut world, &mut physics_world, dt);
+    let reboarded = board_ship(&mut world, &mut physics_world, astronaut);
+    step_physics(&mut world, &mut physics_world, dt);
+    let ship_body = *world
+        .get::<&RigidBodyHandle>(ship)
+        .expect("Ship should have a body");
+    remove_rigid_body(&mut physics_world, ship_body);
+    world.despawn(ship).expect("Ship should exist");
+
+    let left_wreck = exit_ship(&mut world, astronaut);
+    step_physics(&mut world, &mut physics_world, dt);
+    let still_boarded = world.satisfies::<&Boarded>(astronaut).unwrap_or(true);
+    let has_body = world
+        .satisfies::<&RigidBodyHandle>(astronaut)
+        .unwrap_or(false);
+    let final_position = world
+        .get::<&Transform>(astronaut)
+        .expect("Astronaut should exist")
+        .position;
+
+    println!(
+        "eva-airlock: boarded {:?}, exited {:?} at {:.2}, reboarded {:?}",
+        boarded, exited, exit_position, reboarded
+    );
+    println!(
+        "eva-airlock: left the destroyed ship {:?}, still boarded {}, has a body {}, at {:.2}",
+        left_wreck, still_boarded, has_body, final_position
+    );
+
+    assert!(
+        boarded == Some(ship)
+            && exited == Some(ship)
+            && exit_position.distance(ship_position + airlock_offset) < 1e-3
+            && reboarded == Some(ship)
+            && left_wreck == Some(ship)
+            && !still_boarded
+            && has_body
+            && final_position.distance(ship_position) < 0.1
+    );
+}
+
+// An astronaut in mag boots walking across a drifting, turning hull should stay on its surface
+// and cover the walking distance relative to it
+#[test]
+fn eva_boots() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let hull = world.spawn((
+        Transform {
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        MassProperties::new(50000.0),
+        BoxCollider::new(20.0, 1.0, 20.0),
+        Velocity {
+            linear: Vec3::new(5.0, 0.0, 0.0),
+            angular: Vec3::new(0.0, 0.2, 0.0),
+        },
+        Forces::ZERO,
+    ));
+
+    let character = EvaCharacter::new(0.6, 0.3, 100.0);
+    let standing_height = 0.5 + character.foot_distance();
+    let walk_speed = character.walk_speed;
+    let astronaut = world.spawn((
+        Transform {
+            position: Vec3::new(0.0, standing_height, 0.0),
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        character,
+        EvaInput {
+            local_move: Vec3::Z,
+            local_rotation: Quat::IDENTITY,
+        },
+    ));
+
+    let elapsed = 2.0;
+    for _ in 0..120 {
+        step_physics(&mut world, &mut physics_world, elapsed / 120.0);
+    }
+
+    let (hull_position, hull_orientation) = {
+        let transform = world.get::<&Transform>(hull).expect("Hull should exist");
+        (transform.position, transform.orientation)
+    };
+    let astronaut_position = world
+        .get::<&Transform>(astronaut)
+        .expect("Astronaut should exist")
+        .position;
+    let standing_on = world
+        .get::<&EvaCharacter>(astronaut)
+        .expect("Astronaut should exist")
+        .standing_on;
+
+    let relative = hull_orientation.inverse() * (astronaut_position - hull_position);
+    let walked = Vec2::new(relative.x, relative.z).length();
+
+    println!(
+        "eva-boots: astronaut at {:.2} relative to the hull, walked {:.2} m, standing on hull: {}",
+        relative,
+        walked,
+        standing_on == Some(hull)
+    );
+
+    assert!(
+        standing_on == Some(hull)
+            && (relative.y - standing_height).abs() < 0.1
+            && (walked - walk_speed * elapsed).abs() < 0.5
+    );
+}
@@ -0,0 +1,1374 @@
+use std::f32;
+
+use glam::{Mat3, Quat, Vec3};
+use hecs::World;
+use rapier3d::prelude::RigidBodyHandle;
+
+use crate::{
+    core::simulation::step_physics,
+    flight::{
+        avoidance_system::avoidance_system,
+        docking_components::{Docking, DockingEvent, DockingPhase, DockingPort},
+        docking_system::docking_system,
+        flight_assist_system::flight_assist_system,
+        flight_components::{
+            AccelerationControlCommand, FlightAssist, FlightAssistMode, FlightController,
+            FlightControllerGains, PilotInput, SaturationMode, TargetVelocity, ThrustSaturation,
+            ThrusterArray, ThrusterLimits,
+        },
+        flight_controller_system::flight_controller_system,
+        formation_components::{Formation, FormationShape},
+        formation_system::formation_system,
+        navigation_components::{
+            CollisionAvoidance, NavigationEvent, NavigationQueue, NavigationTarget, PathEvent,
+            PathRequest, Pursuit, PursuitLaw, RouteMode,
+        },
+        navigation_system::navigation_system,
+        path_planning_system::path_planning_system,
+        pid::{AntiWindup, PidGains},
+        pursuit_system::pursuit_system,
+        thrust_allocation::{allocate_thrust, thrust_wrench},
+        thruster_system::thruster_system,
+        waypoint_system::waypoint_system,
+    },
+    physics::{
+        physics_components::{
+            BoxCollider, Docked, Forces, InertiaProperties, MassProperties, Velocity,
+        },
+        physics_config::PhysicsConfig,
+        physics_world::PhysicsWorld,
+        sync_physics::remove_rigid_body,
+        transform::Transform,
+    },
+};
+
+use crate::tests::{HULL, gains, spawn_body, spawn_fixed, spawn_ship};
+
+// A ship should report arriving once it has stopped at its target facing the right way, then
+// hold station there without drifting out and re-approaching. Nudging the target within the
+// hysteresis keeps it arrived, moving the target away makes it depart and arrive again.
+#[test]
+fn arrival_hold() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let target = Vec3::new(0.0, 0.0, 40.0);
+    let facing = Quat::from_rotation_y(f32::consts::FRAC_PI_2);
+    let ship = spawn_ship(
+        &mut world,
+        Vec3::ZERO,
+        20000.0,
+        gains(),
+        (NavigationTarget::new(target, facing, 2.0),),
+    );
+
+    let dt = 1.0 / 60.0;
+    let mut fly = |world: &mut World, seconds: f32| {
+        let mut events = Vec::new();
+        for _ in 0..(seconds / dt) as usize {
+            events.extend(navigation_system(world));
+            flight_controller_system(world, dt);
+            thruster_system(world, dt);
+            step_physics(world, &mut physics_world, dt);
+        }
+        events
+    };
+    let move_target = |world: &mut World, position: Vec3| {
+        world
+            .get::<&mut NavigationTarget>(ship)
+            .expect("Ship should exist")
+            .target_position = position;
+    };
+
+    let approach = fly(&mut world, 40.0);
+    move_target(&mut world, target + Vec3::X * 1.5);
+    let nudged = fly(&mut world, 20.0);
+    move_target(&mut world, target + Vec3::X * 40.0);
+    let moved = fly(&mut world, 40.0);
+
+    let transform = world.get::<&Transform>(ship).expect("Ship should exist");
+    let distance = transform.position.distance(target + Vec3::X * 40.0);
+    let angle = transform.orientation.angle_between(facing).to_degrees();
+    println!(
+        "arrival-hold: approach {:?}, nudged {:?}, moved {:?}",
+        approach, nudged, moved
+    );
+    println!(
+        "arrival-hold: final distance {:.2} m, angle {:.2} degrees",
+        distance, angle
+    );
+
+    assert!(
+        approach == [NavigationEvent::Arrived(ship)]
+            && nudged.is_empty()
+            && moved
+                == [
+                    NavigationEvent::Departed(ship),
+                    NavigationEvent::Arrived(ship),
+                ]
+            && distance < 2.0
+            && angle < 5.0
+    );
+}
+
+// Ships spaced around a circle all flying to the opposite side meet in the middle. Without
+// avoidance their hulls hit, with it they should slip past each other and still get there.
+#[test]
+fn avoidance() {
+    let cross_circle = |avoid: bool| {
+        let mut world = World::new();
+        let mut physics_world = PhysicsWorld::new();
+
+        let count = 8;
+        let mut ships = Vec::new();
+        for index in 0..count {
+            let angle = index as f32 / count as f32 * f32::consts::TAU;
+            let start = Vec3::new(angle.cos(), 0.0, angle.sin()) * 80.0;
+            let ship = spawn_ship(
+                &mut world,
+                start,
+                40000.0,
+                gains(),
+                (NavigationTarget::new(-start, Quat::IDENTITY, 2.0),),
+            );
+            if avoid {
+                world
+                    .insert_one(ship, CollisionAvoidance::new())
+                    .expect("Ship should exist");
+            }
+            ships.push((ship, -start));
+        }
+
+        let dt = 1.0 / 60.0;
+        let mut closest = f32::INFINITY;
+        step_physics(&mut world, &mut physics_world, dt);
+        for _ in 0..(90.0 / dt) as usize {
+            navigation_system(&mut world);
+            avoidance_system(&mut world, dt);
+            flight_controller_system(&mut world, dt);
+            thruster_system(&mut world, dt);
+            step_physics(&mut world, &mut physics_world, dt);
+
+            let positions: Vec<Vec3> = ships
+                .iter()
+                .map(|(ship, _goal)| {
+                    world
+                        .get::<&Transform>(*ship)
+                        .expect("Ship should exist")
+                        .position
+                })
+                .collect();
+            for (index, first) in positions.iter().enumerate() {
+                for second in &positions[index + 1..] {
+                    closest = closest.min(first.distance(*second));
+                }
+            }
+        }
+
+        let worst_miss = ships
+            .iter()
+            .map(|(ship, goal)| {
+                world
+                    .get::<&Transform>(*ship)
+                    .expect("Ship should exist")
+                    .position
+                    .distance(*goal)
+            })
+            .fold(0.0, f32::max);
+        (closest, worst_miss)
+    };
+
+    // Two bounding spheres touching
+    let contact = HULL.length();
+    let (closest_without, _miss_without) = cross_circle(false);
+    let (closest_with, miss_with) = cross_circle(true);
+    println!(
+        "avoidance: closest pass {:.2} m without avoidance, {:.2} m with, spheres touch at {:.2} m",
+        closest_without, closest_with, contact
+    );
+    println!("avoidance: furthest ship from its goal {:.2} m", miss_with);
+
+    assert!(closest_without < contact && closest_with > contact && miss_with < 3.0);
+}
+
+// A ship with a strong main drive and a weak retro should plan its approach around the retro
+// and stop at the target rather than sail past it
+#[test]
+fn braking() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let target = Vec3::new(0.0, 0.0, 200.0);
+    let ship = spawn_ship(
+        &mut world,
+        Vec3::ZERO,
+        20000.0,
+        FlightControllerGains::new(PidGains::new(5.0, 0.0, 0.0), PidGains::new(2.0, 0.0, 0.5)),
+        (
+            ThrusterLimits::asymmetric(
+                Vec3::new(20000.0, 20000.0, 100000.0),
+                Vec3::new(20000.0, 20000.0, 10000.0),
+                Vec3::splat(50000.0),
+                Vec3::splat(50000.0),
+            ),
+            NavigationTarget::new(target, Quat::IDENTITY, 2.0),
+        ),
+    );
+
+    let dt = 1.0 / 60.0;
+    let mut furthest = 0.0_f32;
+    step_physics(&mut world, &mut physics_world, dt);
+    for _ in 0..(40.0 / dt) as usize {
+        navigation_system(&mut world);
+        flight_controller_system(&mut world, dt);
+        thruster_system(&mut world, dt);
+        step_physics(&mut world, &mut physics_world, dt);
+
+        let position = world
+            .get::<&Transform>(ship)
+            .expect("Ship should exist")
+            .position;
+        furthest = furthest.max(position.z);
+    }
+
+    let position = world
+        .get::<&Transform>(ship)
+        .expect("Ship should exist")
+        .position;
+    let overshoot = furthest - target.z;
+    println!(
+        "braking: overshoot {:.2} m, final distance from target {:.2} m",
+        overshoot,
+        position.distance(target)
+    );
+
+    assert!(overshoot < 10.0 && position.distance(target) < 3.0);
+}
+
+// A ship cruising on its throttle. Half throttle should hold half the max speed along the
+// nose, strafing shouldn't disturb it, full throttle stops at the max speed, and matching
+// another ship's speed follows it until the throttle is touched.
+#[test]
+fn cruise_control() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let max_speed = 40.0;
+    let ship = spawn_ship(
+        &mut world,
+        Vec3::ZERO,
+        20000.0,
+        FlightControllerGains::new(
+            PidGains::new(5.0, 0.5, 0.0).with_output_limit(4.0),
+            PidGains::new(2.0, 0.5, 0.5).with_output_limit(1.0),
+        ),
+        (
+            NavigationTarget::new(Vec3::ZERO, Quat::IDENTITY, 2.0),
+            FlightAssist::new()
+                .with_mode(FlightAssistMode::Cruise)
+                .with_max_speed(max_speed),
+            PilotInput::ZERO,
+        ),
+    );
+
+    // Drifting alongside, off to one side
+    let other = spawn_body(
+        &mut world,
+        Vec3::new(60.0, 0.0, 0.0),
+        5000.0,
+        HULL,
+        Vec3::new(0.0, 0.0, 12.0),
+    );
+
+    let dt = 1.0 / 60.0;
+    let mut fly = |world: &mut World, local_move: Vec3, seconds: f32| {
+        world
+            .get::<&mut PilotInput>(ship)
+            .expect("Ship should exist")
+            .local_move = local_move;
+        for _ in 0..(seconds / dt) as usize {
+            navigation_system(world);
+            flight_assist_system(world, dt);
+            flight_controller_system(world, dt);
+            thruster_system(world, dt);
+            step_physics(world, &mut physics_world, dt);
+        }
+        world
+            .get::<&Velocity>(ship)
+            .expect("Ship should exist")
+            .linear
+    };
+
+    // A second of forward input takes the throttle to about half
+    fly(&mut world, Vec3::Z, 1.0);
+    let setpoint = world
+        .get::<&FlightAssist>(ship)
+        .expect("Ship should exist")
+        .cruise_speed();
+    let half = fly(&mut world, Vec3::ZERO, 20.0);
+    fly(&mut world, Vec3::X, 10.0);
+    let strafing = world
+        .get::<&Velocity>(ship)
+        .expect("Ship should exist")
+        .linear;
+    fly(&mut world, Vec3::ZERO, 10.0);
+
+    fly(&mut world, Vec3::Z, 3.0);
+    let full = fly(&mut world, Vec3::ZERO, 15.0);
+    let full_throttle = world
+        .get::<&FlightAssist>(ship)
+        .expect("Ship should exist")
+        .throttle;
+
+    world
+        .get::<&mut FlightAssist>(ship)
+        .expect("Ship should exist")
+        .match_target = Some(other);
+    let matched = fly(&mut world, Vec3::ZERO, 20.0);
+    fly(&mut world, Vec3::NEG_Z, 0.1);
+    let still_matching = world
+        .get::<&FlightAssist>(ship)
+        .expect("Ship should exist")
+        .match_target
+        .is_some();
+
+    println!(
+        "cruise-control: half throttle {:.2} m/s for {:.2}, strafing {:.2} m/s forward and {:.2} m/s sideways",
+        half.z, setpoint, strafing.z, strafing.x
+    );
+    println!(
+        "cruise-control: full throttle {:.2} m/s of {:.0} at throttle {:.2}",
+        full.z, max_speed, full_throttle
+    );
+    println!(
+        "cruise-control: matched {:.2} m/s against 12 m/s, still matching after throttle input {}",
+        matched.z, still_matching
+    );
+
+    assert!(
+        (setpoint - max_speed * 0.5).abs() < 1.0
+            && (half.z - setpoint).abs() < 0.2
+            && half.truncate().length() < 0.1
+            && (strafing.z - setpoint).abs() < 0.5
+            && (strafing.x - 20.0).abs() < 0.5
+            && full_throttle == 1.0
+            && (full.z - max_speed).abs() < 0.2
+            && (matched.z - 12.0).abs() < 0.2
+            && !still_matching
+    );
+}
+
+// A ship docks at a port on the side of a spinning station, lining up at the standoff point
+// and closing in along the port's axis while matching the spin. Knocked sideways out of the
+// corridor on the way in, it should abort, go back out and dock on the second try.
+#[test]
+fn docking() {
+    let mut world = World::new();
+    // The station spins slower than bodies are put to sleep at
+    let mut physics_world = PhysicsWorld::with_config(PhysicsConfig {
+        can_sleep: false,
+        ..PhysicsConfig::load("src/assets/config/physics.cfg")
+    });
+
+    let station = world.spawn((
+        Transform {
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        MassProperties::new(1.0e9),
+        BoxCollider::new(80.0, 80.0, 80.0),
+        Velocity {
+            linear: Vec3::ZERO,
+            angular: Vec3::new(0.0, 0.01, 0.0),
+        },
+        Forces::ZERO,
+        DockingPort::new(
+            Vec3::new(41.0, 0.0, 0.0),
+            Quat::from_rotation_y(f32::consts::FRAC_PI_2),
+        ),
+    ));
+
+    let ship = spawn_ship(
+        &mut world,
+        Vec3::new(150.0, 30.0, 80.0),
+        40000.0,
+        gains(),
+        (
+            NavigationTarget::new(Vec3::ZERO, Quat::IDENTITY, 2.0),
+            Docking::new(station),
+        ),
+    );
+
+    let dt = 1.0 / 60.0;
+    let mut events = Vec::new();
+    let mut knocked = false;
+    let mut docked_at = None;
+    step_physics(&mut world, &mut physics_world, dt);
+    for frame in 0..(240.0 / dt) as usize {
+        for event in docking_system(&mut world) {
+            if event == DockingEvent::Docked(ship) {
+                docked_at = Some(frame as f32 * dt);
+            }
+            events.push(event);
+        }
+
+        // Halfway in, shove the ship across the corridor
+        let (transform, forces, docking, nav_target) = world
+            .query_one_mut::<(&Transform, &mut Forces, &Docking, &NavigationTarget)>(ship)
+            .expect("Ship should exist");
+        if !knocked
+            && docking.phase == DockingPhase::Approaching
+            && transform.position.distance(nav_target.target_position) < 25.0
+        {
+            forces.apply_impulse_at_point(Vec3::Y * 12.0 * 5000.0, transform.position);
+            knocked = true;
+        }
+
+        navigation_system(&mut world);
+        flight_controller_system(&mut world, dt);
+        thruster_system(&mut world, dt);
+        step_physics(&mut world, &mut physics_world, dt);
+    }
+
+    // Where the ship sits in the station's frame, against where the port puts it
+    let station_transform = world
+        .get::<&Transform>(station)
+        .expect("Station should exist");
+    let ship_transform = world.get::<&Transform>(ship).expect("Ship should exist");
+    let to_station = station_transform.orientation.inverse();
+    let local_position = to_station * (ship_transform.position - station_transform.position);
+    let local_orientation = to_station * ship_transform.orientation;
+    let docked_position = Vec3::new(41.0 + 4.3138 * 0.5, 0.0, 0.0);
+    let docked_orientation = Quat::from_rotation_y(-f32::consts::FRAC_PI_2);
+    let offset = local_position.distance(docked_position);
+    let angle = local_orientation
+        .angle_between(docked_orientation)
+        .to_degrees();
+    let aborts = world
+        .get::<&Docking>(ship)
+        .expect("Ship should exist")
+        .aborts;
+    let docked_with = world.get::<&Docked>(ship).map(|docked| docked.with).ok();
+
+    println!("docking: events {:?}", events);
+    match docked_at {
+        Some(time) => println!("docking: docked after {:.1} s", time),
+        None => println!("docking: never docked"),
+    }
+    println!(
+        "docking: {:.2} m and {:.2} degrees off the port in the station's frame, {} aborts",
+        offset, angle, aborts
+    );
+
+    assert!(
+        events == [DockingEvent::Aborted(ship), DockingEvent::Docked(ship)]
+            && aborts == 1
+            && docked_with == Some(station)
+            && offset < 0.5
+            && angle < 5.0
+    );
+}
+
+// A piloted ship through each flight assist mode. Coupled, it flies at the speed the stick
+// asks for and stops when let go. Decoupled, the stick fires the thrusters and the ship coasts
+// on once let go. Rate control turns it while the stick is held and stops it turning after.
+// Back in position hold, it comes to a stop rather than flying on.
+#[test]
+fn flight_assist() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let ship = spawn_ship(
+        &mut world,
+        Vec3::ZERO,
+        20000.0,
+        FlightControllerGains::new(
+            PidGains::new(5.0, 0.5, 0.0).with_output_limit(4.0),
+            PidGains::new(2.0, 0.5, 0.5).with_output_limit(1.0),
+        ),
+        (
+            NavigationTarget::new(Vec3::ZERO, Quat::IDENTITY, 2.0),
+            FlightAssist::new().with_mode(FlightAssistMode::Coupled),
+            PilotInput::ZERO,
+        ),
+    );
+
+    let dt = 1.0 / 60.0;
+    let mut fly = |world: &mut World, local_move: Vec3, local_turn: Vec3, seconds: f32| {
+        *world
+            .get::<&mut PilotInput>(ship)
+            .expect("Ship should exist") = PilotInput {
+            local_move,
+            local_turn,
+        };
+        for _ in 0..(seconds / dt) as usize {
+            navigation_system(world);
+            flight_assist_system(world, dt);
+            flight_controller_system(world, dt);
+            thruster_system(world, dt);
+            step_physics(world, &mut physics_world, dt);
+        }
+        let velocity = world.get::<&Velocity>(ship).expect("Ship should exist");
+        (velocity.linear, velocity.angular)
+    };
+    let set_assist = |world: &mut World, mode: FlightAssistMode, rate_control: bool| {
+        let mut assist = world
+            .get::<&mut FlightAssist>(ship)
+            .expect("Ship should exist");
+        assist.mode = mode;
+        assist.rate_control = rate_control;
+    };
+
+    // Coupled, the stick sets the speed and letting go stops the ship
+    let (coupled_velocity, _) = fly(&mut world, Vec3::Z * 0.4, Vec3::ZERO, 10.0);
+    let (coupled_released, _) = fly(&mut world, Vec3::ZERO, Vec3::ZERO, 15.0);
+
+    // Decoupled, 2 s of full thrust at 4 m/s² and the ship keeps what it gained
+    set_assist(&mut world, FlightAssistMode::Decoupled, false);
+    let (decoupled_velocity, _) = fly(&mut world, Vec3::Z, Vec3::ZERO, 2.0);
+    let (decoupled_coast, _) = fly(&mut world, Vec3::ZERO, Vec3::ZERO, 10.0);
+
+    // Rate control, the stick sets the turn rate and letting go stops the turn
+    set_assist(&mut world, FlightAssistMode::Decoupled, true);
+    let (_, turning) = fly(&mut world, Vec3::ZERO, Vec3::Y * 0.5, 10.0);
+    fly(&mut world, Vec3::ZERO, Vec3::ZERO, 10.0);
+    let held_orientation = world
+        .get::<&Transform>(ship)
+        .expect("Ship should exist")
+        .orientation;
+    let (_, turn_released) = fly(&mut world, Vec3::ZERO, Vec3::ZERO, 5.0);
+    let drift_angle = world
+        .get::<&Transform>(ship)
+        .expect("Ship should exist")
+        .orientation
+        .angle_between(held_orientation)
+        .to_degrees();
+
+    // Position hold, the ship stops wherever it is
+    set_assist(&mut world, FlightAssistMode::PositionHold, false);
+    let (held, _) = fly(&mut world, Vec3::ZERO, Vec3::ZERO, 30.0);
+
+    println!(
+        "flight-assist: coupled {:.2} m/s at 0.4 stick, {:.2} m/s let go",
+        coupled_velocity.length(),
+        coupled_released.length()
+    );
+    println!(
+        "flight-assist: decoupled {:.2} m/s after the burn, {:.2} m/s coasting",
+        decoupled_velocity.length(),
+        decoupled_coast.length()
+    );
+    println!(
+        "flight-assist: rate control {:.3} rad/s at 0.5 stick, {:.3} rad/s let go, {:.2} degrees drift",
+        turning.length(),
+        turn_released.length(),
+        drift_angle
+    );
+    println!(
+        "flight-assist: position hold {:.2} m/s after 30 s",
+        held.length()
+    );
+
+    assert!(
+        (coupled_velocity.z - 20.0).abs() < 0.5
+            && coupled_released.length() < 0.1
+            && (decoupled_velocity.length() - 8.0).abs() < 0.5
+            && (decoupled_coast.length() - decoupled_velocity.length()).abs() < 0.05
+            && (turning.y - 0.3).abs() < 0.02
+            && turn_released.length() < 0.01
+            && drift_angle < 1.0
+            && held.length() < 0.1
+    );
+}
+
+// Followers scattered around a moving leader should settle into a wedge and keep it. When one
+// is destroyed the rest should close the gap without flying into each other.
+#[test]
+fn formation() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let leader = spawn_body(
+        &mut world,
+        Vec3::ZERO,
+        5000.0,
+        HULL,
+        Vec3::new(0.0, 0.0, 5.0),
+    );
+
+    let mut members = Vec::new();
+    for index in 0..6 {
+        let angle = index as f32 / 6.0 * f32::consts::TAU;
+        members.push(spawn_ship(
+            &mut world,
+            Vec3::new(angle.cos(), 0.0, angle.sin()) * 60.0,
+            40000.0,
+            gains(),
+            (NavigationTarget::new(Vec3::ZERO, Quat::IDENTITY, 2.0),),
+        ));
+    }
+    world
+        .insert_one(
+            leader,
+            Formation::new(FormationShape::Wedge { spacing: 20.0 }, members.clone()),
+        )
+        .expect("Leader should exist");
+
+    // Worst slot error and closest approach between any two members over a stretch of flight
+    let dt = 1.0 / 60.0;
+    let fly = |world: &mut World, physics_world: &mut PhysicsWorld, seconds: f32| {
+        let mut closest = f32::INFINITY;
+        for _ in 0..(seconds / dt) as usize {
+            formation_system(world);
+            pursuit_system(world);
+            navigation_system(world);
+            flight_controller_system(world, dt);
+            thruster_system(world, dt);
+            step_physics(world, physics_world, dt);
+
+            let positions: Vec<Vec3> = world
+                .query::<&Transform>()
+                .with::<&Pursuit>()
+                .iter()
+                .map(|(_entity, transform)| transform.position)
+                .collect();
+            for (index, first) in positions.iter().enumerate() {
+                for second in &positions[index + 1..] {
+                    closest = closest.min(first.distance(*second));
+                }
+            }
+        }
+
+        let leader_position = world
+            .get::<&Transform>(leader)
+            .expect("Leader should exist")
+            .position;
+        let formation = world
+            .get::<&Formation>(leader)
+            .expect("Leader should exist");
+        let worst = formation
+            .members
+            .iter()
+            .zip(&formation.slots)
+            .map(|(&member, &slot)| {
+                let position = world
+                    .get::<&Transform>(member)
+                    .expect("Member should exist")
+                    .position;
+                position.distance(leader_position + slot)
+            })
+            .fold(0.0, f32::max);
+        (worst, closest)
+    };
+
+    let (formed_error, _closest) = fly(&mut world, &mut physics_world, 40.0);
+
+    // Lose the member on the leader's right wing, next to it
+    let lost = world
+        .get::<&Formation>(leader)
+        .expect("Leader should exist")
+        .members[0];
+    let rb_handle = *world
+        .get::<&RigidBodyHandle>(lost)
+        .expect("Member should have a body");
+    remove_rigid_body(&mut physics_world, rb_handle);
+    world.despawn(lost).expect("Member should exist");
+
+    let (reformed_error, closest) = fly(&mut world, &mut physics_world, 30.0);
+    let remaining = world
+        .get::<&Formation>(leader)
+        .expect("Leader should exist")
+        .members
+        .len();
+
+    println!(
+        "formation: formed within {:.2} m, {} left reformed within {:.2} m, closest pass {:.2} m",
+        formed_error, remaining, reformed_error, closest
+    );
+
+    assert!(formed_error < 2.0 && remaining == 5 && reformed_error < 2.0 && closest > 10.0);
+}
+
+// A wall stands between a ship and its goal. Flying straight there runs the hull into it, a
+// planned route should take the ship around the edge without touching it. A goal shut inside a
+// box can't be reached at all, the ship should come as close as it can and hold there.
+#[test]
+fn path_planning() {
+    let wall = (Vec3::new(0.0, 0.0, 100.0), Vec3::new(120.0, 120.0, 10.0));
+    let goal = Vec3::new(0.0, 0.0, 200.0);
+
+    // Six walls of a hollow cube around the goal
+    let enclosure: Vec<(Vec3, Vec3)> = [Vec3::X, Vec3::Y, Vec3::Z]
+        .into_iter()
+        .flat_map(|axis| [axis, -axis])
+        .map(|side| {
+            let size = Vec3::splat(44.0) - side.abs() * 40.0;
+            (goal + side * 21.0, size)
+        })
+        .collect();
+
+    let fly_to_goal = |obstacles: &[(Vec3, Vec3)], plan: bool| {
+        let mut world = World::new();
+        let mut physics_world = PhysicsWorld::new();
+
+        for &(center, size) in obstacles {
+            spawn_fixed(&mut world, center, size);
+        }
+
+        let goal_target = NavigationTarget::new(goal, Quat::IDENTITY, 2.0);
+        let ship = spawn_ship(
+            &mut world,
+            Vec3::ZERO,
+            40000.0,
+            gains(),
+            (
+                ThrustSaturation::new(SaturationMode::Uniform),
+                NavigationTarget::new(Vec3::ZERO, Quat::IDENTITY, 2.0),
+            ),
+        );
+        if plan {
+            world
+                .insert_one(ship, PathRequest::new(goal_target))
+                .expect("Ship should exist");
+        } else {
+            world
+                .insert_one(ship, goal_target)
+                .expect("Ship should exist");
+        }
+
+        let dt = 1.0 / 60.0;
+        let mut closest = f32::INFINITY;
+        let mut corners = 0;
+        step_physics(&mut world, &mut physics_world, dt);
+        // The search is spread over frames, keep calling until it reports
+        let mut frames = 1;
+        let mut events = path_planning_system(&mut world, &mut physics_world);
+        while events.is_empty() && frames < 1000 {
+            frames += 1;
+            events = path_planning_system(&mut world, &mut physics_world);
+        }
+        let reached = events.contains(&PathEvent::Planned(ship));
+        if let Ok(queue) = world.get::<&NavigationQueue>(ship) {
+            corners = queue.waypoints.len() - 1;
+        }
+
+        for _ in 0..(60.0 / dt) as usize {
+            waypoint_system(&mut world);
+            navigation_system(&mut world);
+            flight_controller_system(&mut world, dt);
+            thruster_system(&mut world, dt);
+            step_physics(&mut world, &mut physics_world, dt);
+
+            // Gap between the nearest obstacle and the hull's bounding sphere
+            let position = world
+                .get::<&Transform>(ship)
+                .expect("Ship should exist")
+                .position;
+            for &(center, size) in obstacles {
+                let outside = ((position - center).abs() - size * 0.5).max(Vec3::ZERO);
+                closest = closest.min(outside.length() - HULL.length() * 0.5);
+            }
+        }
+
+        let (position, speed) = world
+            .query_one_mut::<(&Transform, &Velocity)>(ship)
+            .map(|(transform, velocity)| (transform.position, velocity.linear.length()))
+            .expect("Ship should exist");
+        (
+            closest,
+            position.distance(goal),
+            speed,
+            corners,
+            reached,
+            frames,
+        )
+    };
+
+    let (closest_direct, _, _, _, _, _) = fly_to_goal(&[wall], false);
+    let (closest_planned, miss_planned, _, corners, reached_planned, _) =
+        fly_to_goal(&[wall], true);
+    let (closest_shut, miss_shut, speed_shut, _, reached_shut, frames_shut) =
+        fly_to_goal(&enclosure, true);
+    println!(
+        "path-planning: closest to the wall {:.2} m flying straight, {:.2} m planned with {} corners",
+        closest_direct, closest_planned, corners
+    );
+    println!(
+        "path-planning: planned route ended {:.2} m from the goal",
+        miss_planned
+    );
+    println!(
+        "path-planning: shut in goal reported {} after {} frames, held {:.2} m from it at {:.2} m/s, {:.2} m clear of the box",
+        if reached_shut {
+            "reachable"
+        } else {
+            "unreachable"
+        },
+        frames_shut,
+        miss_shut,
+        speed_shut,
+        closest_shut
+    );
+
+    assert!(
+        closest_direct <= 0.0
+            && closest_planned > 0.0
+            && corners > 0
+            && miss_planned < 2.0
+            && reached_planned
+            && !reached_shut
+            && frames_shut > 1
+            && frames_shut < 1000
+            && closest_shut > 0.0
+            && miss_shut < 50.0
+            && speed_shut < 0.5
+    );
+}
+
+// A ship told to hold still against a constant sideways push should settle with no drift
+// once the integral term has built up, where PD alone leaves it sliding
+#[test]
+fn pid_disturbance() {
+    let drift_with = |ki: f32| {
+        let mut world = World::new();
+        let mut physics_world = PhysicsWorld::new();
+
+        let gains = FlightControllerGains::new(
+            PidGains::new(5.0, ki, 0.0)
+                .with_output_limit(20.0)
+                .with_anti_windup(AntiWindup::BackCalculation { tracking_time: 1.0 }),
+            PidGains::new(2.0, ki, 0.5).with_output_limit(4.0),
+        );
+        let ship = spawn_ship(&mut world, Vec3::ZERO, 100000.0, gains, ());
+
+        let dt = 1.0 / 60.0;
+        step_physics(&mut world, &mut physics_world, dt);
+        for _ in 0..(20.0 / dt) as usize {
+            flight_controller_system(&mut world, dt);
+            thruster_system(&mut world, dt);
+            world
+                .get::<&mut Forces>(ship)
+                .expect("Ship should exist")
+                .linear += Vec3::new(5000.0, 0.0, 0.0);
+            step_physics(&mut world, &mut physics_world, dt);
+        }
+
+        world
+            .get::<&Velocity>(ship)
+            .expect("Ship should exist")
+            .linear
+            .length()
+    };
+
+    let pd_drift = drift_with(0.0);
+    let pid_drift = drift_with(1.0);
+
+    println!(
+        "pid-disturbance: steady drift with PD {:.4} m/s, with PID {:.4} m/s",
+        pd_drift, pid_drift
+    );
+
+    assert!(pd_drift > 0.1 && pid_drift < 0.01);
+}
+
+// Stiffer gains swapped in halfway through speeding up should take over without a kick in the
+// commanded acceleration. Assigned straight over the old ones, the new proportional term jumps
+// the command the moment they land.
+#[test]
+fn pid_gain_swap() {
+    let stiffer = FlightControllerGains::new(
+        PidGains::new(10.0, 1.0, 0.5).with_derivative_filter(0.2),
+        PidGains::new(4.0, 1.0, 1.0).with_derivative_filter(0.2),
+    );
+
+    // How far the command moved over the step before the swap and over the step it happened on
+    let command_steps = |swap: fn(&mut FlightController, FlightControllerGains)| {
+        let mut world = World::new();
+        let mut physics_world = PhysicsWorld::new();
+
+        let ship = spawn_ship(
+            &mut world,
+            Vec3::ZERO,
+            20000.0,
+            gains(),
+            (TargetVelocity::new(
+                Vec3::new(20.0, 0.0, 10.0),
+                Vec3::new(0.0, 0.5, 0.0),
+            ),),
+        );
+
+        let dt = 1.0 / 60.0;
+        let swap_frame = (2.0 / dt) as usize;
+        let mut commands = Vec::new();
+        step_physics(&mut world, &mut physics_world, dt);
+        for frame in 0..=swap_frame {
+            if frame == swap_frame {
+                swap(
+                    &mut world
+                        .get::<&mut FlightController>(ship)
+                        .expect("Ship should exist"),
+                    stiffer,
+                );
+            }
+            flight_controller_system(&mut world, dt);
+            let command = world
+                .get::<&AccelerationControlCommand>(ship)
+                .expect("Ship should exist");
+            commands.push((command.linear_acceleration, command.angular_acceleration));
+            drop(command);
+            thruster_system(&mut world, dt);
+            step_physics(&mut world, &mut physics_world, dt);
+        }
+
+        let step = |frame: usize| {
+            let ((linear_a, angular_a), (linear_b, angular_b)) =
+                (commands[frame - 1], commands[frame]);
+            linear_a.distance(linear_b) + angular_a.distance(angular_b)
+        };
+        (step(swap_frame - 1), step(swap_frame))
+    };
+
+    let (before, transferred) = command_steps(FlightController::set_gains);
+    let (_, assigned) = command_steps(|controller, gains| controller.gains = gains);
+
+    println!(
+        "pid-gain-swap: command moved {:.3} the step before, {:.3} on the swap with set_gains, {:.3} assigning the gains",
+        before, transferred, assigned
+    );
+
+    // Twice the proportional gain moves the command about twice as far per step on its own
+    assert!(transferred < before * 3.0 && assigned > transferred * 10.0);
+}
+
+// A ship with weak thrusters told to speed up sharply saturates for a while. With no output
+// limit only the thrusters' saturation can tell the integral to stop, and without it fed back
+// the integral winds up and carries the ship well past the target speed.
+#[test]
+fn pid_windup() {
+    let overshoot_with = |saturation: Option<ThrustSaturation>| {
+        let mut world = World::new();
+        let mut physics_world = PhysicsWorld::new();
+
+        let gains =
+            FlightControllerGains::new(PidGains::new(2.0, 2.0, 0.0), PidGains::new(2.0, 0.5, 0.5));
+        let ship = spawn_ship(
+            &mut world,
+            Vec3::ZERO,
+            5000.0,
+            gains,
+            (TargetVelocity::new(Vec3::new(10.0, 0.0, 0.0), Vec3::ZERO),),
+        );
+        if let Some(saturation) = saturation {
+            world
+                .insert_one(ship, saturation)
+                .expect("Ship should exist");
+        }
+
+        let dt = 1.0 / 60.0;
+        let mut fastest = 0.0_f32;
+        step_physics(&mut world, &mut physics_world, dt);
+        for _ in 0..(30.0 / dt) as usize {
+            flight_controller_system(&mut world, dt);
+            thruster_system(&mut world, dt);
+            step_physics(&mut world, &mut physics_world, dt);
+            fastest = fastest.max(
+                world
+                    .get::<&Velocity>(ship)
+                    .expect("Ship should exist")
+                    .linear
+                    .x,
+            );
+        }
+
+        (fastest - 10.0) / 10.0
+    };
+
+    let unaware = overshoot_with(None);
+    let fed_back = overshoot_with(Some(ThrustSaturation::new(SaturationMode::Uniform)));
+
+    println!(
+        "pid-windup: overshoot without saturation fed back {:.1}%, with it {:.1}%",
+        unaware * 100.0,
+        fed_back * 100.0
+    );
+
+    assert!(unaware > 0.2 && fed_back < 0.05);
+}
+
+// Every pursuit law should catch a target crossing in front of the ship, with lead pursuit and
+// proportional navigation cutting the corner that pure pursuit chases round. Rendezvous should
+// end up parked behind the target, moving with it.
+#[test]
+fn pursuit() {
+    let chase = |law: PursuitLaw, seconds: f32| {
+        let mut world = World::new();
+        let mut physics_world = PhysicsWorld::new();
+
+        let target = spawn_body(
+            &mut world,
+            Vec3::new(150.0, 0.0, 0.0),
+            5000.0,
+            HULL,
+            Vec3::new(0.0, 0.0, 10.0),
+        );
+        let ship = spawn_ship(
+            &mut world,
+            Vec3::ZERO,
+            40000.0,
+            gains(),
+            (
+                NavigationTarget::new(Vec3::ZERO, Quat::IDENTITY, 2.0),
+                Pursuit::new(target, law).with_closing_speed(15.0),
+            ),
+        );
+
+        let dt = 1.0 / 60.0;
+        let mut caught_at = None;
+        let mut arrived = false;
+        step_physics(&mut world, &mut physics_world, dt);
+        for step in 0..(seconds / dt) as usize {
+            pursuit_system(&mut world);
+            arrived |= navigation_system(&mut world).contains(&NavigationEvent::Arrived(ship));
+            flight_controller_system(&mut world, dt);
+            thruster_system(&mut world, dt);
+            step_physics(&mut world, &mut physics_world, dt);
+
+            let position = |entity| {
+                world
+                    .get::<&Transform>(entity)
+                    .expect("Entity should exist")
+                    .position
+            };
+            if caught_at.is_none() && position(ship).distance(position(target)) < 8.0 {
+                caught_at = Some(step as f32 * dt);
+            }
+        }
+
+        let state = |entity| {
+            let mut query = world
+                .query_one::<(&Transform, &Velocity)>(entity)
+                .expect("Entity should exist");
+            let (transform, velocity) = query.get().expect("Entity should have a velocity");
+            (transform.position, velocity.linear)
+        };
+        let (ship_position, ship_velocity) = state(ship);
+        let (target_position, target_velocity) = state(target);
+        (
+            caught_at,
+            arrived,
+            ship_position - target_position,
+            ship_velocity - target_velocity,
+        )
+    };
+
+    let mut intercepts = Vec::new();
+    for (name, law) in [
+        ("pure", PursuitLaw::Pure),
+        ("lead", PursuitLaw::Lead),
+        (
+            "proportional navigation",
+            PursuitLaw::ProportionalNavigation {
+                navigation_constant: 4.0,
+            },
+        ),
+    ] {
+        let (caught_at, _arrived, _offset, _relative_velocity) = chase(law, 60.0);
+        match caught_at {
+            Some(time) => println!("pursuit: {} caught the target after {:.2} s", name, time),
+            None => println!("pursuit: {} never caught the target", name),
+        }
+        intercepts.push(caught_at.unwrap_or(f32::INFINITY));
+    }
+
+    let offset = Vec3::new(0.0, 0.0, -20.0);
+    let (_caught_at, arrived, rendezvous_offset, relative_velocity) = chase(
+        PursuitLaw::Rendezvous {
+            local_offset: offset,
+        },
+        60.0,
+    );
+    println!(
+        "pursuit: rendezvous ended {:.2} m from its slot at {:.3} m/s relative, arrived {}",
+        rendezvous_offset.distance(offset),
+        relative_velocity.length(),
+        arrived
+    );
+
+    assert!(
+        intercepts.iter().all(|time| time.is_finite())
+            && intercepts[1] < intercepts[0]
+            && intercepts[2] < intercepts[0]
+            && arrived
+            && rendezvous_offset.distance(offset) < 2.0
+            && relative_velocity.length() < 0.5
+    );
+}
+
+// The RCS layout should hit a reachable wrench exactly, and once the engines have spooled up
+// a ship flying on it should accelerate as commanded without picking up spin
+#[test]
+fn thrust_allocation() {
+    let half_extents = HULL / 2.0;
+    let mut thrusters = ThrusterArray::rcs_layout(half_extents, 12500.0, 50000.0, 50000.0);
+    let limits = thrusters.limits();
+
+    let requested_force = Vec3::new(20000.0, -10000.0, 60000.0);
+    let requested_torque = Vec3::new(5000.0, 0.0, -8000.0);
+    let throttles = allocate_thrust(
+        &thrusters.thrusters,
+        requested_force,
+        requested_torque,
+        &limits,
+    );
+    for (thruster, throttle) in thrusters.thrusters.iter_mut().zip(&throttles) {
+        thruster.throttle = *throttle;
+    }
+    let (force, torque) = thrust_wrench(&thrusters.thrusters);
+    let in_range = throttles
+        .iter()
+        .all(|throttle| (0.0..=1.0).contains(throttle));
+
+    println!(
+        "thrust-allocation: requested force {:.0} torque {:.0}, got force {:.0} torque {:.0}",
+        requested_force, requested_torque, force, torque
+    );
+
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+    let acceleration = Vec3::new(1.0, 0.0, 2.0);
+
+    let mut command = AccelerationControlCommand::new();
+    command.linear_acceleration = acceleration;
+    let ship = spawn_ship(
+        &mut world,
+        Vec3::ZERO,
+        0.0,
+        gains(),
+        (
+            limits,
+            ThrusterArray::rcs_layout(half_extents, 12500.0, 50000.0, 50000.0),
+            command,
+        ),
+    );
+
+    let velocity_of = |world: &World| {
+        let velocity = world.get::<&Velocity>(ship).expect("Ship should exist");
+        (velocity.linear, velocity.angular)
+    };
+
+    // Two seconds to spool up, then measure over the third
+    let dt = 1.0 / 60.0;
+    step_physics(&mut world, &mut physics_world, dt);
+    let mut spooled_velocity = Vec3::ZERO;
+    for step in 0..180 {
+        if step == 120 {
+            spooled_velocity = velocity_of(&world).0;
+        }
+        thruster_system(&mut world, dt);
+        step_physics(&mut world, &mut physics_world, dt);
+    }
+
+    let (linear, angular) = velocity_of(&world);
+    let measured_acceleration = linear - spooled_velocity;
+
+    println!(
+        "thrust-allocation: ship acceleration {:.3} for {:.3} commanded, spin {:.4}",
+        measured_acceleration, acceleration, angular
+    );
+
+    assert!(
+        in_range
+            && force.distance(requested_force) < 1.0
+            && torque.distance(requested_torque) < 1.0
+            && measured_acceleration.distance(acceleration) < 0.05
+            && angular.length() < 1e-3
+    );
+}
+
+// A diagonal command past the limits should keep its direction when scaled, where per axis
+// clamping bends it. With rotation priority on a thruster array the torque should come through
+// whole and the force shrink to fit what is left, still pointing the same way.
+#[test]
+fn thrust_saturation() {
+    let mut world = World::new();
+
+    let mut spawn_commanded = |limits: ThrusterLimits, array: Option<ThrusterArray>, mode| {
+        let mut command = AccelerationControlCommand::new();
+        command.linear_acceleration = Vec3::new(3.0, 1.0, 0.0);
+        command.angular_acceleration = Vec3::new(0.0, 2.5, 0.0);
+
+        let ship = world.spawn((
+            Transform {
+                position: Vec3::ZERO,
+                orientation: Quat::IDENTITY,
+                scale: Vec3::ONE,
+            },
+            MassProperties::new(5000.0),
+            InertiaProperties::new(Mat3::from_diagonal(Vec3::splat(20000.0))),
+            Forces::ZERO,
+            command,
+            limits,
+            ThrustSaturation::new(mode),
+        ));
+        if let Some(array) = array {
+            world.insert_one(ship, array).expect("Ship should exist");
+        }
+        ship
+    };
+
+    let box_limits = || ThrusterLimits::new(Vec3::splat(5000.0), Vec3::splat(50000.0));
+    let per_axis = spawn_commanded(box_limits(), None, SaturationMode::PerAxis);
+    let uniform = spawn_commanded(box_limits(), None, SaturationMode::Uniform);
+
+    // Weak RCS so the yaw and the sideways push compete for the same nozzles
+    let array = ThrusterArray::rcs_layout(Vec3::new(4.0, 0.5, 2.0), 2500.0, 20000.0, 20000.0);
+    let prioritised = spawn_commanded(
+        array.limits(),
+        Some(array),
+        SaturationMode::RotationPriority {
+            rotation_priority: 1.0,
+        },
+    );
+
+    thruster_system(&mut world, 1.0);
+
+    let commanded = Vec3::new(3.0, 1.0, 0.0);
+    let off_course = |force: Vec3| force.angle_between(commanded).to_degrees();
+    let box_force = |ship| {
+        world
+            .get::<&Forces>(ship)
+            .expect("Ship should exist")
+            .linear
+    };
+
+    let per_axis_force = box_force(per_axis);
+    let uniform_force = box_force(uniform);
+
+    let (array_force, array_torque) = thrust_wrench(
+        &world
+            .get::<&ThrusterArray>(prioritised)
+            .expect("Ship should exist")
+            .thrusters,
+    );
+    let saturation = world
+        .get::<&ThrustSaturation>(prioritised)
+        .map(|saturation| {
+            (
+                saturation.saturated,
+                saturation.linear_scale,
+                saturation.angular_scale,
+                saturation.linear_authority,
+            )
+        })
+        .expect("Ship should exist");
+
+    println!(
+        "thrust-saturation: per axis {:.0} ({:.1} deg off), uniform {:.0} ({:.1} deg off)",
+        per_axis_force,
+        off_course(per_axis_force),
+        uniform_force,
+        off_course(uniform_force)
+    );
+    println!(
+        "thrust-saturation: rotation priority force {:.0} ({:.1} deg off) torque {:.0}, \
+         saturation {:?}",
+        array_force,
+        off_course(array_force),
+        array_torque,
+        saturation
+    );
+
+    let (saturated, _linear_scale, angular_scale, linear_authority) = saturation;
+    assert!(
+        off_course(per_axis_force) > 5.0
+            && off_course(uniform_force) < 0.1
+            && uniform_force.distance(Vec3::new(5000.0, 5000.0 / 3.0, 0.0)) < 1.0
+            && off_course(array_force) < 0.5
+            && saturated
+            && angular_scale > 0.999
+            && linear_authority < 1.0
+            && (array_torque.y - 50000.0).abs() < 100.0
+    );
+}
+
+// A ping-pong route should be flown out and back in order, stopping at the ends and keeping
+// up speed through the pass-through waypoint in the middle
+#[test]
+fn waypoint_route() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let ends = [Vec3::new(0.0, 0.0, 60.0), Vec3::new(60.0, 0.0, 0.0)];
+    let middle = Vec3::new(60.0, 0.0, 60.0);
+    let mut route = NavigationQueue::new().with_mode(RouteMode::PingPong);
+    route.add_waypoint(NavigationTarget::new(ends[0], Quat::IDENTITY, 2.0));
+    route.add_waypoint(
+        NavigationTarget::new(middle, Quat::IDENTITY, 5.0).with_pass_through_speed(8.0),
+    );
+    route.add_waypoint(NavigationTarget::new(ends[1], Quat::IDENTITY, 2.0));
+
+    let ship = spawn_ship(
+        &mut world,
+        Vec3::ZERO,
+        20000.0,
+        FlightControllerGains::new(PidGains::new(5.0, 0.0, 0.0), PidGains::new(2.0, 0.0, 0.5)),
+        (
+            NavigationTarget::new(Vec3::ZERO, Quat::IDENTITY, 2.0),
+            route,
+        ),
+    );
+
+    // Each time the target moves on, note where the ship was heading and how fast it went
+    let dt = 1.0 / 60.0;
+    let mut reached = Vec::new();
+    let mut heading_to = None;
+    step_physics(&mut world, &mut physics_world, dt);
+    for _ in 0..(50.0 / dt) as usize {
+        waypoint_system(&mut world);
+        let target = world
+            .get::<&NavigationTarget>(ship)
+            .expect("Ship should exist")
+            .target_position;
+        let speed = world
+            .get::<&Velocity>(ship)
+            .expect("Ship should exist")
+            .linear
+            .length();
+        if let Some(previous) = heading_to
+            && previous != target
+        {
+            reached.push((previous, speed));
+        }
+        heading_to = Some(target);
+
+        navigation_system(&mut world);
+        flight_controller_system(&mut world, dt);
+        thruster_system(&mut world, dt);
+        step_physics(&mut world, &mut physics_world, dt);
+    }
+
+    for (position, speed) in &reached {
+        println!(
+            "waypoint-route: reached {:.0} at {:.2} m/s",
+            position, speed
+        );
+    }
+
+    let expected = [ends[0], middle, ends[1], middle, ends[0]];
+    let in_order = reached.len() >= expected.len()
+        && reached
+            .iter()
+            .zip(expected)
+            .all(|((position, _speed), expected)| *position == expected);
+    let kept_speed = reached
+        .iter()
+        .filter(|(position, _speed)| *position == middle)
+        .all(|(_position, speed)| *speed > 4.0);
+    let stopped = reached
+        .iter()
+        .filter(|(position, _speed)| *position != middle)
+        .all(|(_position, speed)| *speed <= 0.5);
+
+    assert!(in_order && kept_speed && stopped);
+}
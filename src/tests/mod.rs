@@ -0,0 +1,100 @@
+// Headless simulation tests. Each one builds a small world, flies or steps it for a while and
+// checks the outcome, printing what it measured so a failure says how far off it was.
+
+mod destruction;
+mod eva;
+mod flight;
+mod physics;
+
+use glam::{Quat, Vec3};
+use hecs::{DynamicBundle, Entity, World};
+
+use crate::{
+    flight::{
+        flight_components::{
+            AccelerationControlCommand, FlightController, FlightControllerGains, TargetVelocity,
+            ThrusterLimits,
+        },
+        pid::PidGains,
+    },
+    physics::{
+        physics_components::{BoxCollider, FixedBody, Forces, MassProperties, Velocity},
+        transform::Transform,
+    },
+};
+
+// The albatross's hull
+pub const HULL: Vec3 = Vec3::new(9.5484, 1.28, 4.3138);
+
+// Gains most tests fly with, integral terms included
+pub fn gains() -> FlightControllerGains {
+    FlightControllerGains::new(PidGains::new(5.0, 0.5, 0.0), PidGains::new(2.0, 0.5, 0.5))
+}
+
+// A 5 t ship with the albatross's hull at rest, thrusters pushing up to `max_force` along each
+// axis and a flight controller. `extra` is added on top, for navigation, flight assist and the
+// like.
+pub fn spawn_ship(
+    world: &mut World,
+    position: Vec3,
+    max_force: f32,
+    gains: FlightControllerGains,
+    extra: impl DynamicBundle,
+) -> Entity {
+    let ship = world.spawn((
+        Transform {
+            position,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        MassProperties::new(5000.0),
+        BoxCollider::new(HULL.x, HULL.y, HULL.z),
+        Velocity::ZERO,
+        Forces::ZERO,
+        ThrusterLimits::new(Vec3::splat(max_force), Vec3::splat(50000.0)),
+        TargetVelocity::new(Vec3::ZERO, Vec3::ZERO),
+        FlightController::new(gains),
+        AccelerationControlCommand::new(),
+    ));
+    world.insert(ship, extra).expect("Ship should exist");
+    ship
+}
+
+// A box drifting at `velocity` with nothing flying it
+pub fn spawn_body(
+    world: &mut World,
+    position: Vec3,
+    mass: f32,
+    extents: Vec3,
+    velocity: Vec3,
+) -> Entity {
+    world.spawn((
+        Transform {
+            position,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        MassProperties::new(mass),
+        BoxCollider::new(extents.x, extents.y, extents.z),
+        Velocity {
+            linear: velocity,
+            angular: Vec3::ZERO,
+        },
+        Forces::ZERO,
+    ))
+}
+
+// An immovable box, for walls and obstacles
+pub fn spawn_fixed(world: &mut World, position: Vec3, extents: Vec3) -> Entity {
+    world.spawn((
+        Transform {
+            position,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        MassProperties::new(1.0),
+        BoxCollider::new(extents.x, extents.y, extents.z),
+        Velocity::ZERO,
+        FixedBody,
+    ))
+}
@@ -0,0 +1,731 @@
+use std::f32;
+
+use glam::{Quat, Vec3, Vec4};
+use hecs::World;
+use miniquad::KeyCode;
+use rapier3d::prelude::{ColliderHandle, Point, RigidBodyHandle, Vector};
+
+use crate::{
+    core::{
+        debug_menu::PhysicsDebugMenu,
+        simulation::{step_physics, step_physics_with},
+    },
+    physics::{
+        collision_layers::CollisionLayer,
+        physics_components::{
+            BoxCollider, CcdMode, CollisionFilter, ContinuousCollision, Damping, Docked, Explosion,
+            Falloff, FixedBody, Forces, MassProperties, OneWayShield, PhysicsMaterial,
+            RotatingFrame, SurfaceVelocity, Velocity,
+        },
+        physics_world::PhysicsWorld,
+        transform::Transform,
+    },
+    render::physics_debug_overlay::PhysicsDebugOverlay,
+};
+
+use crate::tests::{HULL, spawn_body, spawn_fixed};
+
+// A ship moving at 1 km/s towards a 10 cm wall covers several times its own length per
+// substep, so without CCD it tunnels straight through
+#[test]
+fn ccd_wall() {
+    let without_ccd = fire_at_wall(HULL, 1000.0, ContinuousCollision::new(CcdMode::Never));
+    let with_ccd = fire_at_wall(
+        HULL,
+        1000.0,
+        ContinuousCollision::new(CcdMode::AboveSpeed(50.0)),
+    );
+
+    println!(
+        "ccd-wall: without CCD the ship ended at z = {:.2}, with CCD at z = {:.2} (wall at z = 0)",
+        without_ccd, with_ccd
+    );
+
+    assert!(without_ccd > 0.0 && with_ccd < 0.0);
+}
+
+// A 5 cm plate at 60 m/s moves ten times its thickness per substep and slips through the wall
+// between two steps. Predicting contacts a couple of metres ahead should catch it with full
+// CCD left off.
+#[test]
+fn soft_ccd() {
+    let plate = Vec3::new(2.0, 2.0, 0.05);
+    let without_prediction = fire_at_wall(plate, 60.0, ContinuousCollision::new(CcdMode::Never));
+    let with_prediction = fire_at_wall(
+        plate,
+        60.0,
+        ContinuousCollision::with_soft_ccd(CcdMode::Never, 2.0),
+    );
+
+    println!(
+        "soft-ccd: without prediction the plate ended at z = {:.2}, with it at z = {:.2} (wall at z = 0)",
+        without_prediction, with_prediction
+    );
+
+    assert!(without_prediction > 0.0 && with_prediction < 0.0);
+}
+
+// Returns the body's final z position
+fn fire_at_wall(extents: Vec3, speed: f32, ccd: ContinuousCollision) -> f32 {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    spawn_fixed(&mut world, Vec3::ZERO, Vec3::new(200.0, 200.0, 0.1));
+
+    let body = spawn_body(
+        &mut world,
+        Vec3::new(0.0, 0.0, -50.0),
+        5000.0,
+        extents,
+        Vec3::Z * speed,
+    );
+    world.insert_one(body, ccd).expect("Body should exist");
+
+    for _ in 0..120 {
+        step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+    }
+
+    let transform = world.get::<&Transform>(body).expect("Body should exist");
+    transform.position.z
+}
+
+// A projectile fired from inside its own ship should fly out through the hull and hit the ship
+// ahead, and debris tumbling through a sensor should trip it without pushing it around
+#[test]
+fn collision_layers() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let mut spawn = |position: Vec3, mass: f32, extents: Vec3, speed: f32| {
+        spawn_body(&mut world, position, mass, extents, Vec3::Z * speed)
+    };
+    let shooter = spawn(Vec3::ZERO, 5000.0, HULL, 0.0);
+    let target = spawn(Vec3::Z * 40.0, 5000.0, HULL, 0.0);
+    let projectile = spawn(Vec3::Z * -1.0, 50.0, Vec3::splat(0.2), 100.0);
+    let sensor = spawn(Vec3::new(100.0, 0.0, 20.0), 1000.0, Vec3::splat(4.0), 0.0);
+    let debris = spawn(Vec3::new(100.0, 0.0, 0.0), 500.0, Vec3::ONE, 20.0);
+
+    for (entity, filter) in [
+        (shooter, CollisionFilter::new(CollisionLayer::Ships)),
+        (target, CollisionFilter::new(CollisionLayer::Ships)),
+        (
+            projectile,
+            CollisionFilter::with_owner(CollisionLayer::Projectiles, shooter),
+        ),
+        (sensor, CollisionFilter::new(CollisionLayer::Sensors)),
+        (debris, CollisionFilter::new(CollisionLayer::Debris)),
+    ] {
+        world.insert_one(entity, filter).expect("Body should exist");
+    }
+
+    let mut sensor_touched = false;
+    for _ in 0..120 {
+        step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+
+        let collider = |entity| {
+            *world
+                .get::<&ColliderHandle>(entity)
+                .expect("Body should have a collider")
+        };
+        sensor_touched |= physics_world
+            .narrow_phase
+            .contact_pair(collider(sensor), collider(debris))
+            .is_some_and(|pair| pair.has_any_active_contact);
+    }
+
+    let speed = |entity| {
+        world
+            .get::<&Velocity>(entity)
+            .expect("Body should exist")
+            .linear
+            .length()
+    };
+    let debris_z = world
+        .get::<&Transform>(debris)
+        .expect("Debris should exist")
+        .position
+        .z;
+    println!(
+        "collision-layers: shooter speed {:.3}, target speed {:.3}, sensor speed {:.3}, debris at z = {:.1}, sensor touched {}",
+        speed(shooter),
+        speed(target),
+        speed(sensor),
+        debris_z,
+        sensor_touched
+    );
+
+    assert!(
+        speed(shooter) < 1e-3
+            && speed(target) > 0.1
+            && speed(sensor) < 1e-3
+            && debris_z > 30.0
+            && sensor_touched
+    );
+}
+
+// The overlay should draw nothing while off. Once on, every collider gets a wireframe and
+// each body gets velocity, force and point force arrows starting where they act.
+#[test]
+fn debug_overlay() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let mut spawn = |position: Vec3, velocity: Vec3| {
+        spawn_body(&mut world, position, 1000.0, Vec3::splat(2.0), velocity)
+    };
+    let moving = spawn(Vec3::ZERO, Vec3::Z * 10.0);
+    spawn(Vec3::X * 10.0, Vec3::ZERO);
+
+    let dt = 1.0 / 60.0;
+    let mut overlay = PhysicsDebugOverlay::new();
+    let mut frame = |world: &mut World, overlay: &mut PhysicsDebugOverlay| {
+        {
+            let mut forces = world.get::<&mut Forces>(moving).expect("Body should exist");
+            forces.linear = Vec3::X * 20000.0;
+            forces.add_force_at_point(Vec3::Y * 20000.0, Vec3::new(0.0, 0.0, 1.0));
+        }
+        let mut center = Vec3::ZERO;
+        step_physics_with(world, &mut physics_world, dt, |world, physics_world| {
+            overlay.collect(world, physics_world);
+            let handle = *world
+                .get::<&RigidBodyHandle>(moving)
+                .expect("Body should have a rigid body");
+            let coords = physics_world.bodies[handle].center_of_mass().coords;
+            center = Vec3::new(coords.x, coords.y, coords.z);
+        });
+        center
+    };
+
+    frame(&mut world, &mut overlay);
+    let drawn_while_off = overlay.lines.vertices.len();
+
+    overlay.toggle();
+    let center = frame(&mut world, &mut overlay);
+    let lines: Vec<(Vec3, Vec3, Vec4)> = overlay
+        .lines
+        .vertices
+        .chunks_exact(2)
+        .map(|pair| (pair[0].position, pair[1].position, pair[0].color))
+        .collect();
+
+    // Arrows are the only lines in these colours, rapier draws the rest
+    let velocity_color = Vec4::new(0.2, 1.0, 0.2, 1.0);
+    let force_color = Vec4::new(1.0, 0.2, 0.2, 1.0);
+    let point_force_color = Vec4::new(1.0, 0.6, 0.1, 1.0);
+    let arrow_from = |color: Vec4, origin: Vec3, direction: Vec3| {
+        lines.iter().any(|&(a, b, line_color)| {
+            line_color == color
+                && a.distance(origin) < 1e-3
+                && (b - a).normalize_or_zero().dot(direction) > 0.99
+        })
+    };
+    let rapier_lines = lines
+        .iter()
+        .filter(|(_, _, color)| ![velocity_color, force_color, point_force_color].contains(color))
+        .count();
+
+    println!(
+        "debug-overlay: {} vertices while off, {} rapier lines and {} arrow lines while on",
+        drawn_while_off,
+        rapier_lines,
+        lines.len() - rapier_lines
+    );
+
+    // Two boxes, each with twelve wireframe edges and twelve AABB edges
+    assert!(
+        drawn_while_off == 0
+            && rapier_lines >= 48
+            && arrow_from(velocity_color, center, Vec3::Z)
+            && arrow_from(force_color, center, Vec3::X)
+            && arrow_from(point_force_color, Vec3::Z, Vec3::Y)
+    );
+}
+
+// A blast off to one side of a crate should push it away and set it spinning, while an
+// identical crate behind a wall should not move at all
+#[test]
+fn explosion() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let mut spawn_crate =
+        |position: Vec3| spawn_body(&mut world, position, 100.0, Vec3::splat(2.0), Vec3::ZERO);
+    let exposed = spawn_crate(Vec3::new(5.0, 1.5, 0.0));
+    let shielded = spawn_crate(Vec3::new(-6.0, 0.0, 0.0));
+
+    spawn_fixed(
+        &mut world,
+        Vec3::new(-3.0, 0.0, 0.0),
+        Vec3::new(0.5, 10.0, 10.0),
+    );
+
+    // Let the bodies register with the query pipeline before the blast
+    step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+    world.spawn((Explosion::new(
+        Vec3::ZERO,
+        20.0,
+        500.0,
+        0.0,
+        Falloff::Linear,
+    ),));
+    step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+
+    let velocity_of = |world: &World, entity| {
+        let velocity = world.get::<&Velocity>(entity).expect("Crate should exist");
+        (velocity.linear, velocity.angular)
+    };
+
+    let (exposed_linear, exposed_angular) = velocity_of(&world, exposed);
+    let (shielded_linear, shielded_angular) = velocity_of(&world, shielded);
+
+    println!(
+        "explosion: exposed crate v = {:.2} w = {:.2}, shielded crate v = {:.2} w = {:.2}",
+        exposed_linear, exposed_angular, shielded_linear, shielded_angular
+    );
+
+    assert!(
+        exposed_linear.x > 0.1
+            && exposed_angular.length() > 0.01
+            && shielded_linear.length() < 1e-3
+            && shielded_angular.length() < 1e-3
+    );
+}
+
+// A one-way shield plate should let a crate through from behind and stop one coming at its
+// face. A crate with its own shield facing away from the plate should pass through the face
+// too, since either shield forbidding the contact wins. Docked ships overlapping each other
+// shouldn't be pushed apart.
+#[test]
+fn one_way_shield() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    for x in [0.0, 50.0] {
+        world.spawn((
+            Transform {
+                position: Vec3::X * x,
+                orientation: Quat::IDENTITY,
+                scale: Vec3::ONE,
+            },
+            MassProperties::new(1.0),
+            BoxCollider::new(20.0, 20.0, 0.5),
+            Velocity::ZERO,
+            FixedBody,
+            OneWayShield::new(Vec3::Z, 0.5),
+        ));
+    }
+
+    let mut spawn = |position: Vec3, velocity: Vec3, extents: Vec3| {
+        spawn_body(&mut world, position, 100.0, extents, velocity)
+    };
+    let from_behind = spawn(Vec3::new(-5.0, 0.0, -5.0), Vec3::Z * 5.0, Vec3::ONE);
+    let at_face = spawn(Vec3::new(5.0, 0.0, 5.0), Vec3::Z * -5.0, Vec3::ONE);
+    let shielded = spawn(Vec3::new(50.0, 0.0, 5.0), Vec3::Z * -5.0, Vec3::ONE);
+
+    let docked_a = spawn(Vec3::new(100.0, 0.0, 0.0), Vec3::ZERO, HULL);
+    let docked_b = spawn(Vec3::new(100.0, 0.0, 3.0), Vec3::ZERO, HULL);
+    world
+        .insert_one(shielded, OneWayShield::new(Vec3::Z, 0.5))
+        .expect("Crate should exist");
+    world
+        .insert_one(docked_a, Docked { with: docked_b })
+        .expect("Ship should exist");
+
+    for _ in 0..180 {
+        step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+    }
+
+    let z = |body| {
+        world
+            .get::<&Transform>(body)
+            .expect("Body should exist")
+            .position
+            .z
+    };
+    let speed = |body| {
+        world
+            .get::<&Velocity>(body)
+            .expect("Body should exist")
+            .linear
+            .length()
+    };
+    println!(
+        "one-way-shield: from behind ended at z = {:.2}, at the face z = {:.2}, shielded z = {:.2}",
+        z(from_behind),
+        z(at_face),
+        z(shielded)
+    );
+    println!(
+        "one-way-shield: docked ships moving at {:.3} and {:.3}",
+        speed(docked_a),
+        speed(docked_b)
+    );
+
+    assert!(
+        z(from_behind) > 5.0
+            && z(at_face) > 0.0
+            && z(shielded) < -5.0
+            && speed(docked_a) < 1e-3
+            && speed(docked_b) < 1e-3
+    );
+}
+
+// A crate pressed onto a conveyor plate should be carried along at the belt speed. One with
+// the belt on its own underside, like tracks, should crawl the other way across a plain plate.
+// The belt is on a different side of the contact pair in each, so both directions only come
+// out right if the tangent velocity is flipped for the second collider.
+#[test]
+fn surface_velocity() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let conveyor = spawn_fixed(&mut world, Vec3::ZERO, Vec3::new(20.0, 1.0, 20.0));
+    world
+        .insert_one(conveyor, SurfaceVelocity::new(Vec3::X * 2.0))
+        .expect("Plate should exist");
+    let carried = spawn_body(&mut world, Vec3::Y, 100.0, Vec3::ONE, Vec3::ZERO);
+
+    spawn_fixed(&mut world, Vec3::X * 50.0, Vec3::new(20.0, 1.0, 20.0));
+    let tracked = spawn_body(
+        &mut world,
+        Vec3::new(50.0, 1.0, 0.0),
+        100.0,
+        Vec3::ONE,
+        Vec3::ZERO,
+    );
+    world
+        .insert_one(tracked, SurfaceVelocity::new(Vec3::X * 2.0))
+        .expect("Crate should exist");
+
+    // Nothing pulls the crates down in space, so press them onto the belts
+    for _ in 0..120 {
+        for crate_entity in [carried, tracked] {
+            world
+                .get::<&mut Forces>(crate_entity)
+                .expect("Crate should exist")
+                .linear = Vec3::NEG_Y * 1000.0;
+        }
+        step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+    }
+
+    let velocity = |crate_entity| {
+        world
+            .get::<&Velocity>(crate_entity)
+            .expect("Crate should exist")
+            .linear
+    };
+    println!(
+        "surface-velocity: crate on the conveyor {:.2}, crate on tracks {:.2}",
+        velocity(carried),
+        velocity(tracked)
+    );
+
+    assert!(
+        velocity(carried).distance(Vec3::X * 2.0) < 0.1
+            && velocity(tracked).distance(Vec3::NEG_X * 2.0) < 0.1
+    );
+}
+
+// The config file should reach rapier, and the debug menu should change it at runtime. The
+// global CCD switch turns CCD off for every body, and turning it back on restores each body's
+// own setting instead of overriding it.
+#[test]
+fn physics_config() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+    let loaded = physics_world
+        .integration_parameters
+        .num_solver_iterations
+        .get()
+        == 4
+        && physics_world.config.substeps == 2
+        && physics_world.config.ccd_enabled;
+
+    let mut spawn_ship =
+        |x: f32| spawn_body(&mut world, Vec3::new(x, 0.0, 0.0), 5000.0, HULL, Vec3::ZERO);
+    let default_ship = spawn_ship(0.0);
+    let never_ship = spawn_ship(20.0);
+    let always_ship = spawn_ship(40.0);
+    world
+        .insert_one(never_ship, ContinuousCollision::new(CcdMode::Never))
+        .expect("Ship should exist");
+    world
+        .insert_one(always_ship, ContinuousCollision::new(CcdMode::Always))
+        .expect("Ship should exist");
+
+    let dt = 1.0 / 60.0;
+    let ccd_flags = |world: &World, physics_world: &PhysicsWorld| {
+        [default_ship, never_ship, always_ship].map(|ship| {
+            let handle = world
+                .get::<&RigidBodyHandle>(ship)
+                .expect("Ship should have a body");
+            physics_world.bodies[*handle].is_ccd_enabled()
+        })
+    };
+
+    step_physics(&mut world, &mut physics_world, dt);
+    let initial = ccd_flags(&world, &physics_world);
+
+    // F1 opens the menu, substeps is the fifth entry and ccd_enabled the ninth
+    let mut menu = PhysicsDebugMenu::new();
+    let mut press = |keys: &[KeyCode], physics_world: &mut PhysicsWorld| {
+        for &key in keys {
+            if menu.handle_key(key, &mut physics_world.config) {
+                physics_world.apply_config();
+            }
+        }
+    };
+    press(
+        &[KeyCode::F1, KeyCode::Down, KeyCode::Down, KeyCode::Down],
+        &mut physics_world,
+    );
+    press(&[KeyCode::Down, KeyCode::Right], &mut physics_world);
+    press(
+        &[KeyCode::Down, KeyCode::Down, KeyCode::Down, KeyCode::Down],
+        &mut physics_world,
+    );
+    press(&[KeyCode::Right], &mut physics_world);
+    let after_apply = ccd_flags(&world, &physics_world);
+    step_physics(&mut world, &mut physics_world, dt);
+    let switched_off = ccd_flags(&world, &physics_world);
+
+    press(&[KeyCode::Right], &mut physics_world);
+    step_physics(&mut world, &mut physics_world, dt);
+    let switched_on = ccd_flags(&world, &physics_world);
+
+    println!(
+        "physics-config: CCD [default, never, always] initially {:?}, after turning the switch off {:?} then {:?}, back on {:?}",
+        initial, after_apply, switched_off, switched_on
+    );
+    println!(
+        "physics-config: loaded {}, substeps now {}",
+        loaded, physics_world.config.substeps
+    );
+
+    assert!(
+        loaded
+            && physics_world.config.substeps == 3
+            && initial == [true, false, true]
+            && after_apply == initial
+            && switched_off == [false, false, false]
+            && switched_on == [true, false, true]
+    );
+}
+
+// Crates thrown at a wall. Rubbery rock should bounce back off it and metal should stop dead,
+// a crate switched from metal to rubber in flight should bounce, damping should bleed off
+// speed, and changing a material's density at runtime should update the body's mass.
+#[test]
+fn physics_materials() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    spawn_fixed(&mut world, Vec3::ZERO, Vec3::new(200.0, 200.0, 0.1));
+
+    let mut spawn_crate = |x: f32, velocity: Vec3, material: PhysicsMaterial| {
+        let crate_entity = spawn_body(
+            &mut world,
+            Vec3::new(x, 0.0, -10.0),
+            100.0,
+            Vec3::ONE,
+            velocity,
+        );
+        world
+            .insert_one(crate_entity, material)
+            .expect("Crate should exist");
+        crate_entity
+    };
+    let towards_wall = Vec3::Z * 10.0;
+    let rubber = spawn_crate(-20.0, towards_wall, PhysicsMaterial::rubbery_rock());
+    let metal = spawn_crate(0.0, towards_wall, PhysicsMaterial::metal());
+    let switched = spawn_crate(20.0, towards_wall, PhysicsMaterial::metal());
+    let damped = spawn_crate(40.0, Vec3::Y * 10.0, PhysicsMaterial::metal());
+    let undamped = spawn_crate(60.0, Vec3::Y * 10.0, PhysicsMaterial::metal());
+    world
+        .insert_one(damped, Damping::new(0.5, 0.5))
+        .expect("Crate should exist");
+
+    let dt = 1.0 / 60.0;
+    for frame in 0..120 {
+        if frame == 30 {
+            *world
+                .get::<&mut PhysicsMaterial>(switched)
+                .expect("Crate should exist") = PhysicsMaterial::rubbery_rock();
+            world
+                .get::<&mut PhysicsMaterial>(undamped)
+                .expect("Crate should exist")
+                .density = Some(500.0);
+        }
+        step_physics(&mut world, &mut physics_world, dt);
+    }
+
+    let velocity = |crate_entity| {
+        world
+            .get::<&Velocity>(crate_entity)
+            .expect("Crate should exist")
+            .linear
+    };
+    let mass = world
+        .get::<&MassProperties>(undamped)
+        .expect("Crate should exist")
+        .mass;
+    println!(
+        "physics-materials: rebound speed rubber {:.2}, metal {:.2}, switched {:.2}",
+        -velocity(rubber).z,
+        -velocity(metal).z,
+        -velocity(switched).z
+    );
+    println!(
+        "physics-materials: coasting speed damped {:.2}, undamped {:.2}, mass after density change {:.1}",
+        velocity(damped).length(),
+        velocity(undamped).length(),
+        mass
+    );
+
+    assert!(
+        -velocity(rubber).z > 6.0
+            && velocity(metal).z.abs() < 1.0
+            && -velocity(switched).z > 6.0
+            && velocity(damped).length() < 5.0
+            && (velocity(undamped).length() - 10.0).abs() < 0.01
+            && (mass - 500.0).abs() < 1.0
+    );
+}
+
+// Local points are measured from the centre of mass, so an impulse there on a body whose mass
+// sits off its origin should push it straight without spinning it. One off to the side spins it
+// about the axis r x J, and the same impulse given in world space should match the local one.
+#[test]
+fn point_forces() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let facing = Quat::from_rotation_y(f32::consts::FRAC_PI_2);
+    let mut spawn_box = |x: f32| {
+        world.spawn((
+            Transform {
+                position: Vec3::new(x, 0.0, 0.0),
+                orientation: facing,
+                scale: Vec3::ONE,
+            },
+            MassProperties::new(1000.0),
+            BoxCollider::new(2.0, 2.0, 2.0),
+            Velocity::ZERO,
+            Forces::ZERO,
+        ))
+    };
+    let centred = spawn_box(0.0);
+    let off_centre = spawn_box(20.0);
+    let world_space = spawn_box(40.0);
+    step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+
+    // A point mass bolted on at local +X pulls the centre of mass a metre off the origin
+    for body in [centred, off_centre, world_space] {
+        let handle = *world
+            .get::<&RigidBodyHandle>(body)
+            .expect("Body should have a rigid body");
+        physics_world.bodies[handle].set_additional_mass_properties(
+            rapier3d::prelude::MassProperties::new(
+                Point::new(2.0, 0.0, 0.0),
+                1000.0,
+                Vector::zeros(),
+            ),
+            true,
+        );
+    }
+    step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+
+    let impulse = Vec3::Z * 100.0;
+    let side = Vec3::Y;
+    let apply = |world: &mut World, body, apply: &dyn Fn(&mut Forces)| {
+        apply(&mut world.get::<&mut Forces>(body).expect("Body should exist"));
+    };
+    apply(&mut world, centred, &|forces| {
+        forces.apply_local_impulse_at_local_point(impulse, Vec3::ZERO)
+    });
+    apply(&mut world, off_centre, &|forces| {
+        forces.apply_local_impulse_at_local_point(impulse, side)
+    });
+    let world_com = Vec3::new(40.0, 0.0, 0.0) + facing * Vec3::X;
+    apply(&mut world, world_space, &|forces| {
+        forces.apply_impulse_at_point(facing * impulse, world_com + facing * side)
+    });
+    step_physics(&mut world, &mut physics_world, 1.0 / 60.0);
+
+    let velocity = |body| {
+        let velocity = world.get::<&Velocity>(body).expect("Body should exist");
+        (velocity.linear, velocity.angular)
+    };
+    let (centred_linear, centred_angular) = velocity(centred);
+    let (off_linear, off_angular) = velocity(off_centre);
+    let (world_linear, world_angular) = velocity(world_space);
+    println!(
+        "point-forces: at the centre of mass linear {:.3?} angular {:.3?}",
+        centred_linear, centred_angular
+    );
+    println!(
+        "point-forces: off centre linear {:.3?} angular {:.3?}, in world space linear {:.3?} angular {:.3?}",
+        off_linear, off_angular, world_linear, world_angular
+    );
+
+    // Twice the mass after the bolt-on, and the spin should be about the body's local X
+    let expected_linear = facing * impulse / 2000.0;
+    let spin_axis = (facing * side.cross(impulse)).normalize();
+    assert!(
+        centred_linear.distance(expected_linear) < 1e-3
+            && centred_angular.length() < 1e-3
+            && off_linear.distance(expected_linear) < 1e-3
+            && off_angular.normalize_or_zero().dot(spin_axis) > 0.999
+            && world_linear.distance(off_linear) < 1e-3
+            && world_angular.distance(off_angular) < 1e-3
+    );
+}
+
+// Cargo released at rest in a 1 g ring should fall outwards, with the Coriolis force bending
+// its fall against the spin. Seen from outside it just keeps the tangential speed it had when
+// released, which gives the exact radial speed to compare against
+#[test]
+fn rotating_frame() {
+    let mut world = World::new();
+    let mut physics_world = PhysicsWorld::new();
+
+    let radius = 100.0;
+    let spin_rate = RotatingFrame::spin_rate_for_gravity(9.81, radius);
+    world.spawn((
+        Transform {
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        },
+        RotatingFrame::new(Vec3::Z, spin_rate, radius - 20.0, radius + 20.0, 10.0),
+    ));
+
+    let cargo = spawn_body(
+        &mut world,
+        Vec3::new(radius, 0.0, 0.0),
+        10.0,
+        Vec3::splat(1.0),
+        Vec3::ZERO,
+    );
+
+    let elapsed = 1.0;
+    for _ in 0..60 {
+        step_physics(&mut world, &mut physics_world, elapsed / 60.0);
+    }
+
+    let velocity = world
+        .get::<&Velocity>(cargo)
+        .expect("Cargo should exist")
+        .linear;
+
+    let wt = spin_rate * elapsed;
+    let expected_radial_speed = radius * spin_rate * wt / (1.0 + wt * wt).sqrt();
+
+    println!(
+        "rotating-frame: cargo velocity after 1s = {:.2}, expected radial speed {:.2}",
+        velocity, expected_radial_speed
+    );
+
+    // Spin is about +Z, so outward motion along +X is deflected towards -Y
+    assert!((velocity.x - expected_radial_speed).abs() < 0.3 && velocity.y < -0.5);
+}